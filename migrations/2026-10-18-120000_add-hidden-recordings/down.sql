@@ -0,0 +1,2 @@
+ALTER TABLE "recordings"
+      DROP COLUMN "hidden_at";
@@ -0,0 +1,2 @@
+ALTER TABLE "recordings"
+    ADD COLUMN "hidden_at" timestamp with time zone;
@@ -0,0 +1,15 @@
+CREATE TABLE "reports" (
+       id uuid PRIMARY KEY,
+       recording_id uuid NOT NULL REFERENCES "recordings" ("id") ON DELETE CASCADE,
+       reason text NOT NULL,
+       note text,
+       reporter text NOT NULL,
+       created_at timestamp with time zone NOT NULL DEFAULT NOW(),
+       resolved_at timestamp with time zone,
+       resolution text,
+       CONSTRAINT reports_resolved_has_resolution CHECK (("resolved_at" IS NULL) = ("resolution" IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS "reports_reporter_index" ON "reports" ("reporter", "created_at");
+
+CREATE INDEX IF NOT EXISTS "reports_open_index" ON "reports" ("recording_id") WHERE "resolved_at" IS NULL;
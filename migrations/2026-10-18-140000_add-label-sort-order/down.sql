@@ -0,0 +1,10 @@
+ALTER TABLE "categories"
+      DROP COLUMN "sort_order";
+
+ALTER TABLE "genders"
+      DROP COLUMN "sort_order",
+      DROP COLUMN "description";
+
+ALTER TABLE "ages"
+      DROP COLUMN "sort_order",
+      DROP COLUMN "description";
@@ -0,0 +1,10 @@
+ALTER TABLE "ages"
+    ADD COLUMN "description" TEXT,
+    ADD COLUMN "sort_order" smallint NOT NULL DEFAULT 0;
+
+ALTER TABLE "genders"
+    ADD COLUMN "description" TEXT,
+    ADD COLUMN "sort_order" smallint NOT NULL DEFAULT 0;
+
+ALTER TABLE "categories"
+    ADD COLUMN "sort_order" smallint NOT NULL DEFAULT 0;
@@ -0,0 +1,9 @@
+CREATE TABLE "label_translations" (
+       kind text NOT NULL,
+       label_id smallint NOT NULL,
+       locale text NOT NULL,
+       label text NOT NULL,
+       description text,
+       PRIMARY KEY (kind, label_id, locale),
+       CONSTRAINT label_translations_kind CHECK ("kind" IN ('ages', 'categories', 'genders'))
+);
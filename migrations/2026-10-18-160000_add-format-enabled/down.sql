@@ -0,0 +1 @@
+ALTER TABLE "audio_formats" DROP COLUMN "enabled";
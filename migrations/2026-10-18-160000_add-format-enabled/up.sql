@@ -0,0 +1 @@
+ALTER TABLE "audio_formats" ADD COLUMN "enabled" boolean NOT NULL DEFAULT TRUE;
@@ -0,0 +1,8 @@
+-- terms are stored folded, so that the uniqueness constraint ignores
+-- case and accents just like matching does
+CREATE TABLE "blocked_terms" (
+       id serial PRIMARY KEY,
+       term text NOT NULL UNIQUE,
+       whole_word boolean NOT NULL DEFAULT FALSE,
+       created_at timestamp with time zone NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,2 @@
+ALTER TABLE "recordings" DROP CONSTRAINT "recordings_name_skeleton";
+ALTER TABLE "recordings" DROP COLUMN "name_skeleton";
@@ -0,0 +1,6 @@
+-- the skeleton of a name folds case, compatibility characters and
+-- confusable lookalikes, so that names which look alike collide; it
+-- can't be computed in SQL, so run the `backfill-skeletons` helper
+-- after migrating to fill it in for existing recordings
+ALTER TABLE "recordings" ADD COLUMN "name_skeleton" text;
+ALTER TABLE "recordings" ADD CONSTRAINT "recordings_name_skeleton" UNIQUE ("name_skeleton");
@@ -0,0 +1,10 @@
+-- a token can hold one name at a time while its recording is made;
+-- the reservation goes with the token once that's used up
+CREATE TABLE "name_reservations" (
+       token uuid PRIMARY KEY REFERENCES "recording_tokens" (id) ON DELETE CASCADE,
+       name text NOT NULL,
+       name_skeleton text NOT NULL UNIQUE,
+       expires_at timestamp with time zone NOT NULL
+);
+
+CREATE INDEX "name_reservations_expires_at" ON "name_reservations" (expires_at);
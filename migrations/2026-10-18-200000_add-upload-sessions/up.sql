@@ -0,0 +1,14 @@
+-- a resumable upload keeps its token locked until it's finished or
+-- abandoned; the chunks themselves are spooled on disk
+CREATE TABLE "upload_sessions" (
+       id uuid PRIMARY KEY,
+       token uuid NOT NULL UNIQUE REFERENCES "recording_tokens" (id) ON DELETE CASCADE,
+       parent_id uuid NOT NULL REFERENCES "recordings" (id),
+       metadata text NOT NULL,
+       length bigint NOT NULL,
+       received bigint NOT NULL DEFAULT 0,
+       created_at timestamp with time zone NOT NULL DEFAULT NOW(),
+       updated_at timestamp with time zone NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX "upload_sessions_updated_at" ON "upload_sessions" (updated_at);
@@ -0,0 +1,13 @@
+-- a direct upload keeps its token locked until the client has put the
+-- audio in the store itself and asked for it to be finished, or until
+-- it's abandoned
+CREATE TABLE "direct_uploads" (
+       id uuid PRIMARY KEY,
+       token uuid NOT NULL UNIQUE REFERENCES "recording_tokens" (id) ON DELETE CASCADE,
+       parent_id uuid NOT NULL REFERENCES "recordings" (id),
+       metadata text NOT NULL,
+       length bigint NOT NULL,
+       created_at timestamp with time zone NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX "direct_uploads_created_at" ON "direct_uploads" (created_at);
@@ -0,0 +1,7 @@
+-- recordings used to be saved under their bare ID
+ALTER TABLE "recordings" ADD COLUMN "object_key" text;
+
+UPDATE "recordings"
+SET "object_key" = "id"::text
+WHERE "mime_type_id" IS NOT NULL
+  AND "deleted_at" IS NULL;
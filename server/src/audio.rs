@@ -1,9 +1,11 @@
+use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use log::Logger;
 
 use crate::errors::BackendError;
+use crate::metrics;
 
 pub mod format;
 
@@ -21,7 +23,28 @@ pub fn make_wrapper(
 ) -> impl Fn(&[u8]) -> Result<Vec<AudioFormat>, BackendError> {
     let checker = inner::Checker::new(ffprobe_path);
 
-    move |data: &[u8]| checker.identify(logger.clone(), data)
+    move |data: &[u8]| metrics::time_ffprobe(|| checker.identify(logger.clone(), data))
+}
+
+/// Checks that `ffprobe` can be executed.
+pub async fn check_ffprobe(ffprobe: PathBuf) -> Result<(), BackendError> {
+    use tokio::process::Command;
+
+    let output = Command::new(&ffprobe)
+        .arg("-version")
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(BackendError::FfprobeFailed)?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(BackendError::FfprobeFailed(io::Error::new(
+            io::ErrorKind::Other,
+            format!("`ffprobe -version` exited with {}", output.status),
+        )))
+    }
 }
 
 #[cfg(not(use_ffmpeg_sys))]
@@ -0,0 +1,50 @@
+use std::error::Error;
+
+use dotenv::dotenv;
+use log::{info, initialize_logger, warn};
+use structopt::StructOpt;
+
+use backend::config::get_variable;
+use backend::db::{Db, PgDb};
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "backfill-skeletons",
+    about = "Fill in the name skeletons of recordings created before lookalike names collided"
+)]
+struct Opt {}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    dotenv().ok();
+
+    Opt::from_args();
+
+    let logger = initialize_logger();
+
+    let connection_string = get_variable("BACKEND_DB_CONNECTION_STRING");
+    let pool = sqlx::Pool::connect(&connection_string)
+        .await
+        .expect("create database pool from BACKEND_DB_CONNECTION_STRING");
+    let db = PgDb::new(pool);
+
+    info!(logger, "Filling in missing name skeletons...");
+    let lookalikes = db
+        .backfill_name_skeletons()
+        .await
+        .expect("fill in missing name skeletons");
+
+    // these keep their names, and only stop others from taking the
+    // same one exactly
+    for id in &lookalikes {
+        warn!(logger, "Name looks like another, leaving it without a skeleton"; "id" => %id);
+    }
+
+    info!(
+        logger,
+        "Left {} names looking like others without a skeleton",
+        lookalikes.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::error::Error;
+
+use dotenv::dotenv;
+use log::{debug, info, initialize_logger, warn};
+use structopt::StructOpt;
+
+use backend::config::get_variable;
+use backend::db::{Db, PgDb};
+use backend::key::KeyTemplate;
+use backend::replication::backends_from_env;
+use backend::store::Store;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "migrate-keys",
+    about = "Move stored recordings to the keys given by BACKEND_S3_KEY_TEMPLATE"
+)]
+struct Opt {
+    /// Only print which recordings would be moved
+    #[structopt(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    dotenv().ok();
+
+    let opt = Opt::from_args();
+
+    let logger = initialize_logger();
+
+    let connection_string = get_variable("BACKEND_DB_CONNECTION_STRING");
+    let pool = sqlx::Pool::connect(&connection_string)
+        .await
+        .expect("create database pool from BACKEND_DB_CONNECTION_STRING");
+    let db = PgDb::new(pool);
+
+    // every backend is moved directly, rather than through a replicated
+    // store, so that nothing is left to finish in the background
+    let backends = backends_from_env();
+    let (_, primary) = backends.first().expect("name a primary in BACKEND_STORES");
+    let keys = KeyTemplate::from_env().expect("parse BACKEND_S3_KEY_TEMPLATE");
+
+    let recordings = db
+        .retrieve_stored_recordings()
+        .await
+        .expect("retrieve stored recordings");
+
+    info!(logger, "Checking {} stored recordings...", recordings.len());
+
+    let mut moved = 0;
+    let mut failed = 0;
+
+    for recording in recordings {
+        let logger = logger.new(log::o!("id" => format!("{}", recording.id)));
+        let key = keys.render(&recording.id, recording.created_at, &recording.extension);
+
+        if key == recording.key {
+            continue;
+        }
+
+        info!(logger, "Moving {} to {}...", recording.key, key);
+
+        if opt.dry_run {
+            continue;
+        }
+
+        // the copy is only deleted if the database couldn’t be pointed
+        // at it, and the original only once the database no longer
+        // points at it, so a failure part-way through loses nothing
+        if let Err(e) = primary.copy(&recording.key, &key).await {
+            warn!(logger, "Failed to copy object: {}", e);
+            failed += 1;
+            continue;
+        }
+
+        // secondaries missing the copy can be filled in by repair-storage
+        for (name, secondary) in &backends[1..] {
+            if let Err(e) = secondary.copy(&recording.key, &key).await {
+                warn!(logger, "Failed to copy object in {}: {}", name, e);
+            }
+        }
+
+        let url = primary
+            .get_url(&key)
+            .expect("generate URL for moved object");
+
+        if let Err(e) = db
+            .move_object(&recording.id, &recording.key, &key, url.as_ref())
+            .await
+        {
+            warn!(logger, "Failed to point recording at moved object: {}", e);
+            failed += 1;
+
+            for (name, store) in &backends {
+                if let Err(e) = store.delete(&key).await {
+                    warn!(
+                        logger,
+                        "Failed to delete copied object {} in {}: {}", key, name, e
+                    );
+                }
+            }
+
+            continue;
+        }
+
+        for (name, store) in &backends {
+            if let Err(e) = store.delete(&recording.key).await {
+                warn!(
+                    logger,
+                    "Failed to delete original object {} in {}: {}", recording.key, name, e
+                );
+            }
+        }
+
+        debug!(logger, "Moved {} to {}", recording.key, key);
+        moved += 1;
+    }
+
+    info!(logger, "Moved {} recordings, {} failed", moved, failed);
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::time::Duration;
+
+use dotenv::dotenv;
+use log::{info, initialize_logger, warn};
+use structopt::StructOpt;
+use time::OffsetDateTime;
+
+use backend::config::get_variable;
+use backend::db::{Db, PgDb};
+use backend::direct::STAGING_PREFIX;
+use backend::replication::backends_from_env;
+use backend::store::Store;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "reconcile-storage",
+    about = "Find objects without recordings and recordings without objects"
+)]
+struct Opt {
+    /// Only look at objects whose keys start with this
+    #[structopt(long, default_value = "")]
+    prefix: String,
+
+    /// How old an object must be, in seconds, before it’s treated as
+    /// orphaned, so that uploads in progress are left alone
+    #[structopt(long, default_value = "3600")]
+    min_age: u64,
+
+    /// Delete objects that no recording points at
+    #[structopt(long)]
+    delete_orphans: bool,
+
+    /// Forget the objects of recordings whose audio is missing
+    #[structopt(long)]
+    clear_dangling: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    dotenv().ok();
+
+    let opt = Opt::from_args();
+
+    let logger = initialize_logger();
+
+    let connection_string = get_variable("BACKEND_DB_CONNECTION_STRING");
+    let pool = sqlx::Pool::connect(&connection_string)
+        .await
+        .expect("create database pool from BACKEND_DB_CONNECTION_STRING");
+    let db = PgDb::new(pool);
+
+    // secondaries are brought in line with the primary by repair-storage
+    let (_, store) = backends_from_env()
+        .into_iter()
+        .next()
+        .expect("name a primary in BACKEND_STORES");
+
+    // the recordings are read both before and after listing, so that
+    // neither an object saved nor a recording changed in the meantime
+    // is mistaken for a discrepancy
+    let before = db
+        .retrieve_stored_recordings()
+        .await
+        .expect("retrieve stored recordings");
+
+    info!(logger, "Listing objects...");
+    // uploads in progress are cleaned up by the server
+    let objects: Vec<_> = store
+        .list(&opt.prefix)
+        .await
+        .expect("list objects")
+        .into_iter()
+        .filter(|object| !object.key.starts_with(STAGING_PREFIX))
+        .collect();
+
+    let after = db
+        .retrieve_stored_recordings()
+        .await
+        .expect("retrieve stored recordings");
+
+    info!(
+        logger,
+        "Comparing {} objects with {} stored recordings...",
+        objects.len(),
+        after.len()
+    );
+
+    let known: HashSet<String> = before
+        .iter()
+        .chain(after.iter())
+        .map(|recording| recording.key.clone())
+        .collect();
+    let listed: HashSet<&str> = objects.iter().map(|object| object.key.as_str()).collect();
+    let current: HashSet<(_, &str)> = after
+        .iter()
+        .map(|recording| (recording.id, recording.key.as_str()))
+        .collect();
+
+    let cutoff = OffsetDateTime::now_utc() - Duration::from_secs(opt.min_age);
+
+    let orphans: Vec<_> = objects
+        .iter()
+        .filter(|object| !known.contains(&object.key))
+        .filter(|object| match object.last_modified {
+            Some(last_modified) => last_modified < cutoff,
+            None => {
+                warn!(logger, "Skipping object of unknown age"; "key" => &object.key);
+                false
+            }
+        })
+        .collect();
+
+    // recordings outside the prefix weren’t listed, so can’t be judged
+    let dangling: Vec<_> = before
+        .iter()
+        .filter(|recording| recording.key.starts_with(&opt.prefix))
+        .filter(|recording| !listed.contains(recording.key.as_str()))
+        .filter(|recording| current.contains(&(recording.id, recording.key.as_str())))
+        .collect();
+
+    for object in &orphans {
+        info!(logger, "Found orphaned object"; "key" => &object.key);
+
+        if opt.delete_orphans {
+            if let Err(e) = store.delete(&object.key).await {
+                warn!(logger, "Failed to delete orphaned object: {}", e; "key" => &object.key);
+            }
+        }
+    }
+
+    for recording in &dangling {
+        let logger = logger.new(log::o!("id" => format!("{}", recording.id)));
+        info!(logger, "Found recording without object"; "key" => &recording.key);
+
+        if opt.clear_dangling {
+            if let Err(e) = db.detach_object(&recording.id, &recording.key).await {
+                warn!(logger, "Failed to clear recording URL: {}", e);
+            }
+        }
+    }
+
+    info!(
+        logger,
+        "Found {} orphaned objects and {} recordings without objects",
+        orphans.len(),
+        dangling.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+use std::error::Error;
+
+use dotenv::dotenv;
+use log::{debug, info, initialize_logger, warn};
+use structopt::StructOpt;
+
+use backend::config::get_variable;
+use backend::db::{Db, PgDb};
+use backend::direct::STAGING_PREFIX;
+use backend::replication::backends_from_env;
+use backend::store::Store;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "repair-storage",
+    about = "Copy recordings missing from any of the stores in BACKEND_STORES from one that has them"
+)]
+struct Opt {
+    /// Only print which recordings would be copied
+    #[structopt(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    dotenv().ok();
+
+    let opt = Opt::from_args();
+
+    let logger = initialize_logger();
+
+    let connection_string = get_variable("BACKEND_DB_CONNECTION_STRING");
+    let pool = sqlx::Pool::connect(&connection_string)
+        .await
+        .expect("create database pool from BACKEND_DB_CONNECTION_STRING");
+    let db = PgDb::new(pool);
+
+    let backends = backends_from_env();
+
+    if backends.len() < 2 {
+        info!(
+            logger,
+            "Only one store is configured, so there's nothing to repair"
+        );
+        return Ok(());
+    }
+
+    let mut listed = vec![];
+
+    for (name, store) in &backends {
+        info!(logger, "Listing objects in {}...", name);
+
+        let keys: HashSet<String> = store
+            .list("")
+            .await
+            .expect("list objects")
+            .into_iter()
+            .map(|object| object.key)
+            .filter(|key| !key.starts_with(STAGING_PREFIX))
+            .collect();
+
+        listed.push(keys);
+    }
+
+    // only recordings are repaired, as anything else in the stores is
+    // either on its way in or left over
+    let recordings = db
+        .retrieve_stored_recordings()
+        .await
+        .expect("retrieve stored recordings");
+
+    info!(logger, "Checking {} stored recordings...", recordings.len());
+
+    let mut copied = 0;
+    let mut failed = 0;
+    let mut lost = 0;
+
+    for recording in recordings {
+        let logger = logger.new(log::o!("id" => format!("{}", recording.id)));
+
+        let missing: Vec<_> = (0..backends.len())
+            .filter(|&index| !listed[index].contains(&recording.key))
+            .collect();
+
+        if missing.is_empty() {
+            continue;
+        }
+
+        // the primary comes first, so it's preferred as the source
+        let source = match (0..backends.len()).find(|index| !missing.contains(index)) {
+            Some(source) => source,
+            None => {
+                warn!(logger, "Recording is missing from every store"; "key" => &recording.key);
+                lost += 1;
+                continue;
+            }
+        };
+
+        let (source_name, source_store) = &backends[source];
+
+        for &index in &missing {
+            let (name, _) = &backends[index];
+            info!(
+                logger,
+                "Copying {} from {} to {}...", recording.key, source_name, name
+            );
+        }
+
+        if opt.dry_run {
+            continue;
+        }
+
+        let raw = match source_store.load(&recording.key).await {
+            Ok(Some(raw)) => raw,
+            Ok(None) => {
+                warn!(logger, "Object disappeared from {}", source_name; "key" => &recording.key);
+                failed += 1;
+                continue;
+            }
+            Err(e) => {
+                warn!(logger, "Failed to load object from {}: {}", source_name, e; "key" => &recording.key);
+                failed += 1;
+                continue;
+            }
+        };
+
+        for index in missing {
+            let (name, store) = &backends[index];
+
+            match store
+                .save(&recording.key, recording.content_type.clone(), raw.clone())
+                .await
+            {
+                Ok(()) => {
+                    debug!(logger, "Copied {} to {}", recording.key, name);
+                    copied += 1;
+                }
+                Err(e) => {
+                    warn!(logger, "Failed to save object to {}: {}", name, e; "key" => &recording.key);
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    info!(
+        logger,
+        "Made {} copies, {} failed, {} recordings missing everywhere", copied, failed, lost
+    );
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! Filtering of abusive names and text through an admin-managed list
+//! of blocked terms.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use unicode_normalization::char::is_combining_mark;
+
+use crate::normalization::normalize_name;
+
+pub type Id = i32;
+
+/// A blocked term, stored folded.
+#[derive(Clone, Debug, Serialize, sqlx::FromRow)]
+pub struct BlockedTerm {
+    pub(crate) id: Id,
+    pub(crate) term: String,
+
+    /// Whether the term only matches whole words rather than anywhere
+    /// in the text, for short terms that are parts of harmless words.
+    pub(crate) whole_word: bool,
+}
+
+/// A term to add to the blocklist.
+#[derive(Clone, Debug, Deserialize)]
+pub struct NewBlockedTerm {
+    #[serde(deserialize_with = "deserialize_folded")]
+    pub(crate) term: String,
+
+    #[serde(default)]
+    pub(crate) whole_word: bool,
+}
+
+/// Folds text for matching by normalizing it, dropping accents and
+/// lowercasing it.
+///
+/// ```
+/// use backend::blocklist::fold;
+/// assert_eq!(fold(" Éclair "), "eclair");
+/// ```
+pub fn fold(text: impl AsRef<str>) -> String {
+    normalize_name(text)
+        .chars()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Returns whether the text contains any of the blocked terms.
+pub fn is_blocked(terms: &[BlockedTerm], text: &str) -> bool {
+    let text = fold(text);
+
+    terms.iter().any(|t| {
+        if t.whole_word {
+            text.split(|c: char| !c.is_alphanumeric())
+                .any(|word| word == t.term)
+        } else {
+            text.contains(&t.term)
+        }
+    })
+}
+
+fn deserialize_folded<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+    Ok(fold(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_blocked, BlockedTerm};
+
+    fn term(term: &str, whole_word: bool) -> BlockedTerm {
+        BlockedTerm {
+            id: 0,
+            term: term.to_owned(),
+            whole_word,
+        }
+    }
+
+    #[test]
+    fn ignores_case_and_accents() {
+        let terms = vec![term("badword", false)];
+
+        assert!(is_blocked(&terms, "BÂDWÖRD"));
+        assert!(is_blocked(&terms, "a badwords b"));
+        assert!(!is_blocked(&terms, "bad word"));
+    }
+
+    #[test]
+    fn matches_whole_words_only_when_asked() {
+        let terms = vec![term("ass", true)];
+
+        assert!(is_blocked(&terms, "an ass"));
+        assert!(is_blocked(&terms, "ass-like"));
+        assert!(!is_blocked(&terms, "classic"));
+    }
+}
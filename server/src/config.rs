@@ -1,6 +1,8 @@
 use std::env;
 use std::path::PathBuf;
 
+use crate::validation::MetadataLimits;
+
 /// Returns the value of the named environment variable if it exists or panics.
 pub fn get_variable(name: &str) -> String {
     env::var(name).unwrap_or_else(|_| panic!("must define {} environment variable", name))
@@ -19,3 +21,25 @@ pub fn get_ffprobe(env: Option<String>) -> Option<PathBuf> {
 pub fn get_ffprobe(env: Option<String>) -> Option<PathBuf> {
     env.map(PathBuf::from)
 }
+
+/// Returns the metadata limits, overriding the defaults with any of
+/// `BACKEND_MAX_NAME_LENGTH`, `BACKEND_MAX_LOCATION_LENGTH`,
+/// `BACKEND_MAX_OCCUPATION_LENGTH` and `BACKEND_MAX_EMAIL_LENGTH`
+/// that are set.
+pub fn get_metadata_limits() -> MetadataLimits {
+    let defaults = MetadataLimits::default();
+
+    let limit = |name: &str, default: usize| {
+        env::var(name).map_or(default, |v| {
+            v.parse()
+                .unwrap_or_else(|_| panic!("parse {} as usize", name))
+        })
+    };
+
+    MetadataLimits {
+        name: limit("BACKEND_MAX_NAME_LENGTH", defaults.name),
+        location: limit("BACKEND_MAX_LOCATION_LENGTH", defaults.location),
+        occupation: limit("BACKEND_MAX_OCCUPATION_LENGTH", defaults.occupation),
+        email: limit("BACKEND_MAX_EMAIL_LENGTH", defaults.email),
+    }
+}
@@ -1,70 +1,295 @@
+use std::time::Duration;
+
 use futures::future::BoxFuture;
 use url::Url;
 use uuid::Uuid;
 
-use crate::label::Label;
+use crate::blocklist::{self, BlockedTerm, NewBlockedTerm};
+use crate::direct::DirectUpload;
+use crate::key::StoredRecording;
+use crate::label::{Id, Kind, Label, LabelContents, ManagedLabel, Translation};
 use crate::recording::{
-    ChildRecording, NewRecording, PartialRecording, Recording, RecordingToken, UploadMetadata,
+    ChildRecording, ModeratedRecording, NewRecording, PartialRecording, Recording, RecordingToken,
+    UploadMetadata,
+};
+use crate::report::{NewReport, Report, Resolution};
+use crate::reservation::{NewReservation, Reservation};
+use crate::resumable::UploadSession;
+use crate::validation::LabelCheck;
+use crate::{
+    audio::format::AudioFormat,
+    errors::BackendError,
+    mime_type::{FormatContents, ManagedFormat, MimeType},
 };
-use crate::{audio::format::AudioFormat, errors::BackendError, mime_type::MimeType};
 
 pub trait Db {
+    // records that a chunk of a resumable upload was received at the
+    // given offset, returning the new offset, or nothing if the upload
+    // wasn't at that offset
+    fn advance_upload_session(
+        &self,
+        id: &Uuid,
+        offset: u64,
+        length: u64,
+    ) -> BoxFuture<Result<Option<u64>, BackendError>>;
+
+    // fills in the name skeletons of recordings created before they
+    // existed, returning the IDs of any whose names look like others
+    fn backfill_name_skeletons(&self) -> BoxFuture<Result<Vec<Uuid>, BackendError>>;
+
+    // runs a trivial query to make sure the database is reachable
+    fn check(&self) -> BoxFuture<Result<(), BackendError>>;
+
     fn check_availability(&self, name: &str) -> BoxFuture<Result<bool, BackendError>>;
 
+    // reports which of the given labels exist and are enabled, treating
+    // absent optional labels as fine
+    fn check_labels(
+        &self,
+        age_id: Option<Id>,
+        gender_id: Option<Id>,
+        category_id: Id,
+    ) -> BoxFuture<Result<LabelCheck, BackendError>>;
+
     fn children(&self, id: &Uuid) -> BoxFuture<Result<Vec<ChildRecording>, BackendError>>;
 
     fn count_all(&self) -> BoxFuture<Result<i64, BackendError>>;
 
+    // counts the distinct reporters with open reports against the given recording
+    fn count_reporters(&self, id: &Uuid) -> BoxFuture<Result<i64, BackendError>>;
+
     fn create_key(&self, id: &Uuid, email: Option<String>)
         -> BoxFuture<Result<Uuid, BackendError>>;
 
+    fn create_blocked_term(
+        &self,
+        term: NewBlockedTerm,
+    ) -> BoxFuture<Result<blocklist::Id, BackendError>>;
+
+    fn create_format(&self, contents: FormatContents) -> BoxFuture<Result<Id, BackendError>>;
+
+    fn create_label(
+        &self,
+        kind: Kind,
+        contents: LabelContents,
+    ) -> BoxFuture<Result<Id, BackendError>>;
+
+    // fails with `TooManyReports` if the reporter has already made
+    // `per_hour` reports in the last hour
+    fn create_report(
+        &self,
+        id: &Uuid,
+        reporter: &str,
+        report: NewReport,
+        per_hour: u16,
+    ) -> BoxFuture<Result<Uuid, BackendError>>;
+
+    // the token must already be locked, and the ID is chosen up front
+    // so that the request to the store can be signed first
+    fn create_direct_upload(
+        &self,
+        id: &Uuid,
+        parent_id: &Uuid,
+        metadata: &UploadMetadata,
+        length: u64,
+    ) -> BoxFuture<Result<(), BackendError>>;
+
     fn create_token(&self, parent_id: &Uuid) -> BoxFuture<Result<Uuid, BackendError>>;
 
+    // the token must already be locked
+    fn create_upload_session(
+        &self,
+        parent_id: &Uuid,
+        metadata: &UploadMetadata,
+        length: u64,
+    ) -> BoxFuture<Result<Uuid, BackendError>>;
+
+    fn delete_blocked_term(&self, id: blocklist::Id) -> BoxFuture<Result<(), BackendError>>;
+
+    fn delete_direct_upload(&self, id: &Uuid) -> BoxFuture<Result<(), BackendError>>;
+
+    fn delete_format(&self, id: Id) -> BoxFuture<Result<(), BackendError>>;
+
+    fn delete_translation(
+        &self,
+        kind: Kind,
+        id: Id,
+        locale: &str,
+    ) -> BoxFuture<Result<(), BackendError>>;
+
+    // removes the reservations that have expired, returning how many
+    fn delete_expired_reservations(&self) -> BoxFuture<Result<u64, BackendError>>;
+
+    // removes the direct uploads started longer ago than the given
+    // time and releases their tokens, returning their IDs
+    fn delete_stale_direct_uploads(
+        &self,
+        age: Duration,
+    ) -> BoxFuture<Result<Vec<Uuid>, BackendError>>;
+
+    // removes the resumable uploads that haven't received a chunk for
+    // the given time and releases their tokens, returning their IDs
+    fn delete_stale_upload_sessions(
+        &self,
+        idle: Duration,
+    ) -> BoxFuture<Result<Vec<Uuid>, BackendError>>;
+
+    fn delete_upload_session(&self, id: &Uuid) -> BoxFuture<Result<(), BackendError>>;
+
     // this may return multiple backend errors depending on which parts fail
     fn delete(&self, id: &Uuid) -> BoxFuture<Result<(), Vec<BackendError>>>;
 
+    // forgets the object of a recording whose audio has gone missing,
+    // unless it no longer points at the given key
+    fn detach_object(&self, id: &Uuid, key: &str) -> BoxFuture<Result<(), BackendError>>;
+
+    // returns the names not yet taken by any recording or reserved, in
+    // the order given
+    fn filter_available_names(
+        &self,
+        names: Vec<String>,
+    ) -> BoxFuture<Result<Vec<String>, BackendError>>;
+
+    fn hide(&self, id: &Uuid) -> BoxFuture<Result<(), BackendError>>;
+
     fn lock_token(&self, token: &Uuid) -> BoxFuture<Result<Option<Uuid>, BackendError>>;
 
     #[allow(clippy::type_complexity)]
     fn lookup_key(&self, key: &Uuid) -> BoxFuture<Result<Option<(Uuid, Vec<Uuid>)>, BackendError>>;
 
+    // points the recording at the object moved to a new key, unless
+    // it no longer points at the old one
+    fn move_object(
+        &self,
+        id: &Uuid,
+        from: &str,
+        to: &str,
+        url: Option<&Url>,
+    ) -> BoxFuture<Result<(), BackendError>>;
+
     fn insert(
         &self,
         parent_id: &Uuid,
         metadata: UploadMetadata,
     ) -> BoxFuture<Result<NewRecording, BackendError>>;
 
+    // assigns ascending sort orders to the labels in the order given
+    fn reorder_labels(&self, kind: Kind, ids: Vec<Id>) -> BoxFuture<Result<(), BackendError>>;
+
+    // unlike `delete`, this removes every trace of the recording
+    fn purge(&self, id: &Uuid) -> BoxFuture<Result<(), BackendError>>;
+
+    // holds the name for the token, replacing any name it held before
+    fn reserve_name(
+        &self,
+        reservation: NewReservation,
+        duration: Duration,
+    ) -> BoxFuture<Result<Reservation, BackendError>>;
+
+    fn resolve_report(
+        &self,
+        id: &Uuid,
+        resolution: Resolution,
+    ) -> BoxFuture<Result<(), BackendError>>;
+
+    fn restore(&self, id: &Uuid) -> BoxFuture<Result<(), BackendError>>;
+
     fn retrieve(&self, id: &Uuid) -> BoxFuture<Result<Option<Recording>, BackendError>>;
 
-    fn retrieve_ages(&self) -> BoxFuture<Result<Vec<Label>, BackendError>>;
+    fn retrieve_ages(&self, locales: &[String]) -> BoxFuture<Result<Vec<Label>, BackendError>>;
 
-    fn retrieve_categories(&self) -> BoxFuture<Result<Vec<Label>, BackendError>>;
+    fn retrieve_categories(
+        &self,
+        locales: &[String],
+    ) -> BoxFuture<Result<Vec<Label>, BackendError>>;
 
+    fn retrieve_direct_upload(
+        &self,
+        id: &Uuid,
+    ) -> BoxFuture<Result<Option<DirectUpload>, BackendError>>;
+
+    // only includes essences with at least one enabled format
     fn retrieve_format_essences(&self) -> BoxFuture<Result<Vec<String>, BackendError>>;
 
-    fn retrieve_genders(&self) -> BoxFuture<Result<Vec<Label>, BackendError>>;
+    fn retrieve_blocked_terms(&self) -> BoxFuture<Result<Vec<BlockedTerm>, BackendError>>;
+
+    // unlike `retrieve_format_essences`, this includes disabled formats
+    fn retrieve_formats(&self) -> BoxFuture<Result<Vec<ManagedFormat>, BackendError>>;
+
+    fn retrieve_genders(&self, locales: &[String]) -> BoxFuture<Result<Vec<Label>, BackendError>>;
+
+    // unlike `retrieve_ages` etc., this includes disabled labels
+    fn retrieve_labels(&self, kind: Kind) -> BoxFuture<Result<Vec<ManagedLabel>, BackendError>>;
 
     fn retrieve_mime_type(
         &self,
         format: &AudioFormat,
     ) -> BoxFuture<Result<Option<MimeType>, BackendError>>;
 
+    fn retrieve_open_reports(&self) -> BoxFuture<Result<Vec<Report>, BackendError>>;
+
     fn retrieve_random(&self, count: i16)
         -> BoxFuture<Result<Vec<PartialRecording>, BackendError>>;
 
+    fn retrieve_recent(
+        &self,
+        count: i16,
+    ) -> BoxFuture<Result<Vec<ModeratedRecording>, BackendError>>;
+
     fn release_token(&self, token: &Uuid) -> BoxFuture<Result<(), BackendError>>;
 
     fn remove_token(&self, token: &Uuid) -> BoxFuture<Result<(), BackendError>>;
 
+    // lists the recordings with audio in the store, oldest first
+    fn retrieve_stored_recordings(&self) -> BoxFuture<Result<Vec<StoredRecording>, BackendError>>;
+
     fn retrieve_token(
         &self,
         token: &Uuid,
     ) -> BoxFuture<Result<Option<RecordingToken>, BackendError>>;
 
+    fn retrieve_upload_session(
+        &self,
+        id: &Uuid,
+    ) -> BoxFuture<Result<Option<UploadSession>, BackendError>>;
+
+    // creates the translation or replaces an existing one
+    // undoes `advance_upload_session` for a chunk that couldn't be kept,
+    // unless another chunk has been received since
+    fn rewind_upload_session(
+        &self,
+        id: &Uuid,
+        offset: u64,
+        length: u64,
+    ) -> BoxFuture<Result<(), BackendError>>;
+
+    fn save_translation(
+        &self,
+        kind: Kind,
+        id: Id,
+        locale: &str,
+        translation: Translation,
+    ) -> BoxFuture<Result<(), BackendError>>;
+
+    fn update_format(
+        &self,
+        id: Id,
+        contents: FormatContents,
+    ) -> BoxFuture<Result<(), BackendError>>;
+
+    fn update_label(
+        &self,
+        kind: Kind,
+        id: Id,
+        contents: LabelContents,
+    ) -> BoxFuture<Result<(), BackendError>>;
+
+    // private stores have no permanent URL to save
     fn update_url(
         &self,
         id: &Uuid,
-        url: &Url,
+        key: &str,
+        url: Option<&Url>,
         mime_type: MimeType,
     ) -> BoxFuture<Result<(), BackendError>>;
 }
@@ -72,6 +297,8 @@ pub trait Db {
 pub use self::postgres::*;
 
 mod postgres {
+    use std::time::Duration;
+
     use futures::future::BoxFuture;
     use futures::FutureExt;
     use sqlx::{
@@ -82,17 +309,40 @@ mod postgres {
     use url::Url;
     use uuid::Uuid;
 
-    use crate::label::{Id, Label};
+    use crate::blocklist::{self, BlockedTerm, NewBlockedTerm};
+    use crate::direct::DirectUpload;
+    use crate::key::StoredRecording;
+    use crate::label::{Id, Kind, Label, LabelContents, ManagedLabel, Translation};
+    use crate::normalization::{normalize_name, skeleton};
     use crate::recording::{
-        ChildRecording, NewRecording, PartialRecording, Recording, RecordingToken, Times,
-        UploadMetadata,
+        ChildRecording, ModeratedRecording, NewRecording, PartialRecording, Recording,
+        RecordingToken, Times, UploadMetadata,
+    };
+    use crate::report::{NewReport, Reason, Report, Resolution};
+    use crate::reservation::{NewReservation, Reservation};
+    use crate::resumable::UploadSession;
+    use crate::validation::LabelCheck;
+    use crate::{
+        audio::format::AudioFormat,
+        errors::BackendError,
+        mime_type::{FormatContents, ManagedFormat, MimeType},
     };
-    use crate::{audio::format::AudioFormat, errors::BackendError, mime_type::MimeType};
 
     static DEFAULT_URL: Option<String> = None;
 
     const RECORDINGS_ID_CONSTRAINT: &str = "recordings_primary_key";
     const RECORDINGS_NAME_CONSTRAINT: &str = "recordings_name";
+    const RECORDINGS_NAME_SKELETON_CONSTRAINT: &str = "recordings_name_skeleton";
+    const RECORDINGS_PARENT_CONSTRAINT: &str = "recordings_parent_id_fkey";
+    const REPORTS_RECORDING_CONSTRAINT: &str = "reports_recording_id_fkey";
+    const FORMATS_CONSTRAINT: &str = "audio_formats_container_codec_key";
+    const BLOCKED_TERMS_CONSTRAINT: &str = "blocked_terms_term_key";
+    const NAME_RESERVATIONS_CONSTRAINT: &str = "name_reservations_name_skeleton_key";
+    const LABEL_CONSTRAINTS: &[&str] = &[
+        "ages_label_key",
+        "categories_label_key",
+        "genders_label_key",
+    ];
 
     pub struct PgDb {
         pool: PgPool,
@@ -106,6 +356,70 @@ mod postgres {
 
     // these can be simplified once async functions in traits are stabilized
     impl super::Db for PgDb {
+        fn advance_upload_session(
+            &self,
+            id: &Uuid,
+            offset: u64,
+            length: u64,
+        ) -> BoxFuture<Result<Option<u64>, BackendError>> {
+            let id = *id;
+
+            async move {
+                let query = sqlx::query_as(include_str!("queries/advance_upload_session.sql"));
+
+                let received: Option<(i64,)> = query
+                    .bind(id)
+                    .bind(offset as i64)
+                    .bind(length as i64)
+                    .fetch_optional(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(received.map(|(received,)| received as u64))
+            }
+            .boxed()
+        }
+
+        fn backfill_name_skeletons(&self) -> BoxFuture<Result<Vec<Uuid>, BackendError>> {
+            async move {
+                let query = sqlx::query_as::<_, (Uuid, String)>(include_str!(
+                    "queries/retrieve_unskeletonized_names.sql"
+                ));
+
+                let names = query.fetch_all(&self.pool).await.map_err(map_sqlx_error)?;
+                let mut lookalikes = vec![];
+
+                for (id, name) in names {
+                    let result = sqlx::query(include_str!("queries/update_name_skeleton.sql"))
+                        .bind(id)
+                        .bind(skeleton(&name))
+                        .execute(&self.pool)
+                        .await
+                        .map_err(map_sqlx_error);
+
+                    match result {
+                        Ok(_) => {}
+                        Err(BackendError::NameAlreadyExists) => lookalikes.push(id),
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                Ok(lookalikes)
+            }
+            .boxed()
+        }
+
+        fn check(&self) -> BoxFuture<Result<(), BackendError>> {
+            async move {
+                let query = sqlx::query(include_str!("queries/check.sql"));
+
+                query.execute(&self.pool).await.map_err(map_sqlx_error)?;
+
+                Ok(())
+            }
+            .boxed()
+        }
+
         fn check_availability(&self, name: &str) -> BoxFuture<Result<bool, BackendError>> {
             let name = name.to_owned();
 
@@ -114,7 +428,9 @@ mod postgres {
                     sqlx::query_as::<_, (bool,)>(include_str!("queries/check_availability.sql"));
 
                 let (exists,) = query
-                    .bind(name)
+                    .bind(normalize_name(&name))
+                    .bind(skeleton(&name))
+                    .bind(None::<Uuid>)
                     .fetch_one(&self.pool)
                     .await
                     .map_err(map_sqlx_error)?;
@@ -124,6 +440,34 @@ mod postgres {
             .boxed()
         }
 
+        fn check_labels(
+            &self,
+            age_id: Option<Id>,
+            gender_id: Option<Id>,
+            category_id: Id,
+        ) -> BoxFuture<Result<LabelCheck, BackendError>> {
+            async move {
+                let query = sqlx::query_as::<_, (bool, bool, bool)>(include_str!(
+                    "queries/check_labels.sql"
+                ));
+
+                let (age, gender, category) = query
+                    .bind(age_id)
+                    .bind(gender_id)
+                    .bind(category_id)
+                    .fetch_one(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(LabelCheck {
+                    age,
+                    gender,
+                    category,
+                })
+            }
+            .boxed()
+        }
+
         fn children(&self, id: &Uuid) -> BoxFuture<Result<Vec<ChildRecording>, BackendError>> {
             let id = *id;
 
@@ -154,6 +498,24 @@ mod postgres {
             .boxed()
         }
 
+        fn count_reporters(&self, id: &Uuid) -> BoxFuture<Result<i64, BackendError>> {
+            let id = *id;
+
+            async move {
+                let query =
+                    sqlx::query_as::<_, (i64,)>(include_str!("queries/count_reporters.sql"));
+
+                let (count,) = query
+                    .bind(id)
+                    .fetch_one(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(count)
+            }
+            .boxed()
+        }
+
         fn create_key(
             &self,
             id: &Uuid,
@@ -176,6 +538,144 @@ mod postgres {
             .boxed()
         }
 
+        fn create_blocked_term(
+            &self,
+            term: NewBlockedTerm,
+        ) -> BoxFuture<Result<blocklist::Id, BackendError>> {
+            async move {
+                let query = sqlx::query_as(include_str!("queries/create_blocked_term.sql"));
+
+                let (id,): (blocklist::Id,) = query
+                    .bind(&term.term)
+                    .bind(term.whole_word)
+                    .fetch_one(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(id)
+            }
+            .boxed()
+        }
+
+        fn create_format(&self, contents: FormatContents) -> BoxFuture<Result<Id, BackendError>> {
+            async move {
+                let query = sqlx::query_as(include_str!("queries/create_format.sql"));
+
+                let (id,): (Id,) = query
+                    .bind(&contents.container)
+                    .bind(&contents.codec)
+                    .bind(&contents.extension)
+                    .bind(&contents.essence)
+                    .bind(contents.enabled)
+                    .fetch_one(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(id)
+            }
+            .boxed()
+        }
+
+        fn create_label(
+            &self,
+            kind: Kind,
+            contents: LabelContents,
+        ) -> BoxFuture<Result<Id, BackendError>> {
+            async move {
+                let sql = label_query(include_str!("queries/create_label.sql"), kind);
+                let query = sqlx::query_as(&sql);
+
+                let (id,): (Id,) = query
+                    .bind(&contents.label)
+                    .bind(&contents.description)
+                    .bind(contents.enabled)
+                    .fetch_one(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(id)
+            }
+            .boxed()
+        }
+
+        fn create_report(
+            &self,
+            id: &Uuid,
+            reporter: &str,
+            report: NewReport,
+            per_hour: u16,
+        ) -> BoxFuture<Result<Uuid, BackendError>> {
+            let id = *id;
+            let reporter = reporter.to_owned();
+
+            async move {
+                let mut transaction = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+                // reports from the same reporter wait for each other
+                // here, so that none of them is counted too early
+                sqlx::query(include_str!("queries/lock_reporter.sql"))
+                    .bind(&reporter)
+                    .execute(&mut transaction)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                let query = sqlx::query_as(include_str!("queries/create_report.sql"));
+
+                let (report_id,): (Uuid,) = query
+                    .bind(id)
+                    .bind(report.reason.as_str())
+                    .bind(&report.note)
+                    .bind(reporter)
+                    .bind(i64::from(per_hour))
+                    .fetch_optional(&mut transaction)
+                    .await
+                    .map_err(|error| match error {
+                        sqlx::Error::Database(ref e)
+                            if e.constraint() == Some(REPORTS_RECORDING_CONSTRAINT) =>
+                        {
+                            BackendError::NonExistentId(id)
+                        }
+                        _ => map_sqlx_error(error),
+                    })?
+                    .ok_or(BackendError::TooManyReports)?;
+
+                transaction.commit().await.map_err(map_sqlx_error)?;
+
+                Ok(report_id)
+            }
+            .boxed()
+        }
+
+        fn create_direct_upload(
+            &self,
+            id: &Uuid,
+            parent_id: &Uuid,
+            metadata: &UploadMetadata,
+            length: u64,
+        ) -> BoxFuture<Result<(), BackendError>> {
+            let id = *id;
+            let parent_id = *parent_id;
+            let token = metadata.token;
+            let metadata = serde_json::to_string(metadata).expect("serialize upload metadata");
+
+            async move {
+                let query = sqlx::query(include_str!("queries/create_direct_upload.sql"));
+
+                query
+                    .bind(id)
+                    .bind(token)
+                    .bind(parent_id)
+                    .bind(metadata)
+                    .bind(length as i64)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(())
+            }
+            .boxed()
+        }
+
         fn create_token(&self, parent: &Uuid) -> BoxFuture<Result<Uuid, BackendError>> {
             let parent_id = *parent;
 
@@ -193,6 +693,192 @@ mod postgres {
             .boxed()
         }
 
+        fn create_upload_session(
+            &self,
+            parent_id: &Uuid,
+            metadata: &UploadMetadata,
+            length: u64,
+        ) -> BoxFuture<Result<Uuid, BackendError>> {
+            let parent_id = *parent_id;
+            let token = metadata.token;
+            let metadata = serde_json::to_string(metadata).expect("serialize upload metadata");
+
+            async move {
+                let query = sqlx::query_as(include_str!("queries/create_upload_session.sql"));
+
+                let (id,): (Uuid,) = query
+                    .bind(token)
+                    .bind(parent_id)
+                    .bind(metadata)
+                    .bind(length as i64)
+                    .fetch_one(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(id)
+            }
+            .boxed()
+        }
+
+        fn delete_blocked_term(&self, id: blocklist::Id) -> BoxFuture<Result<(), BackendError>> {
+            async move {
+                let query = sqlx::query(include_str!("queries/delete_blocked_term.sql"));
+
+                let result = query
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentBlockedTerm(id))
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+
+        fn delete_direct_upload(&self, id: &Uuid) -> BoxFuture<Result<(), BackendError>> {
+            let id = *id;
+
+            async move {
+                let query = sqlx::query(include_str!("queries/delete_direct_upload.sql"));
+
+                let result = query
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentUpload(id))
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+
+        fn delete_format(&self, id: Id) -> BoxFuture<Result<(), BackendError>> {
+            async move {
+                let query = sqlx::query(include_str!("queries/delete_format.sql"));
+
+                let result = query
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentFormat(id))
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+
+        fn delete_translation(
+            &self,
+            kind: Kind,
+            id: Id,
+            locale: &str,
+        ) -> BoxFuture<Result<(), BackendError>> {
+            let locale = locale.to_owned();
+
+            async move {
+                let query = sqlx::query(include_str!("queries/delete_translation.sql"));
+
+                let result = query
+                    .bind(kind.table())
+                    .bind(id)
+                    .bind(&locale)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentTranslation { kind, id, locale })
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+
+        fn delete_expired_reservations(&self) -> BoxFuture<Result<u64, BackendError>> {
+            async move {
+                let query = sqlx::query(include_str!("queries/delete_expired_reservations.sql"));
+
+                let result = query.execute(&self.pool).await.map_err(map_sqlx_error)?;
+
+                Ok(result.rows_affected())
+            }
+            .boxed()
+        }
+
+        fn delete_stale_direct_uploads(
+            &self,
+            age: Duration,
+        ) -> BoxFuture<Result<Vec<Uuid>, BackendError>> {
+            async move {
+                let query = sqlx::query_as::<_, (Uuid,)>(include_str!(
+                    "queries/delete_stale_direct_uploads.sql"
+                ));
+
+                let ids = query
+                    .bind(age.as_secs() as i64)
+                    .fetch_all(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(ids.into_iter().map(|(id,)| id).collect())
+            }
+            .boxed()
+        }
+
+        fn delete_stale_upload_sessions(
+            &self,
+            idle: Duration,
+        ) -> BoxFuture<Result<Vec<Uuid>, BackendError>> {
+            async move {
+                let query = sqlx::query_as::<_, (Uuid,)>(include_str!(
+                    "queries/delete_stale_upload_sessions.sql"
+                ));
+
+                let ids = query
+                    .bind(idle.as_secs() as i64)
+                    .fetch_all(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(ids.into_iter().map(|(id,)| id).collect())
+            }
+            .boxed()
+        }
+
+        fn delete_upload_session(&self, id: &Uuid) -> BoxFuture<Result<(), BackendError>> {
+            let id = *id;
+
+            async move {
+                let query = sqlx::query(include_str!("queries/delete_upload_session.sql"));
+
+                let result = query
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentUpload(id))
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+
         fn delete(&self, id: &Uuid) -> BoxFuture<Result<(), Vec<BackendError>>> {
             let id = *id;
 
@@ -283,6 +969,29 @@ mod postgres {
             .boxed()
         }
 
+        fn detach_object(&self, id: &Uuid, key: &str) -> BoxFuture<Result<(), BackendError>> {
+            let id = *id;
+            let key = key.to_owned();
+
+            async move {
+                let query = sqlx::query(include_str!("queries/detach_object.sql"));
+
+                let result = query
+                    .bind(id)
+                    .bind(key)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentId(id))
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+
         fn insert(
             &self,
             parent_id: &Uuid,
@@ -291,96 +1000,342 @@ mod postgres {
             let parent_id = *parent_id;
 
             async move {
-                let query = sqlx::query_as(include_str!("queries/create.sql"));
+                let query = sqlx::query_as(include_str!("queries/create.sql"));
+
+                // nothing is inserted if another token reserved the name
+                let (id, created_at, updated_at): (Uuid, OffsetDateTime, OffsetDateTime) = query
+                    .bind(&DEFAULT_URL)
+                    .bind(None::<Option<i16>>)
+                    .bind(&metadata.category_id)
+                    .bind(parent_id)
+                    .bind(&metadata.name)
+                    .bind(&metadata.location)
+                    .bind(&metadata.occupation)
+                    .bind(&metadata.age_id)
+                    .bind(&metadata.gender_id)
+                    .bind(skeleton(&metadata.name))
+                    .bind(metadata.token)
+                    .fetch_optional(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?
+                    .ok_or(BackendError::NameAlreadyExists)?;
+
+                Ok(NewRecording::new(id, created_at, updated_at, metadata))
+            }
+            .boxed()
+        }
+
+        fn filter_available_names(
+            &self,
+            names: Vec<String>,
+        ) -> BoxFuture<Result<Vec<String>, BackendError>> {
+            async move {
+                let query = sqlx::query_as::<_, (String,)>(include_str!(
+                    "queries/filter_available_names.sql"
+                ));
+
+                let skeletons = names.iter().map(skeleton).collect::<Vec<_>>();
+
+                let available = query
+                    .bind(&names)
+                    .bind(&skeletons)
+                    .fetch_all(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(available.into_iter().map(|(name,)| name).collect())
+            }
+            .boxed()
+        }
+
+        fn hide(&self, id: &Uuid) -> BoxFuture<Result<(), BackendError>> {
+            let id = *id;
+
+            async move {
+                let query = sqlx::query(include_str!("queries/hide.sql"));
+
+                let result = query
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentId(id))
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+
+        fn lock_token(&self, token: &Uuid) -> BoxFuture<Result<Option<Uuid>, BackendError>> {
+            let token = *token;
+
+            async move {
+                let query = sqlx::query_as(include_str!("queries/lock_token.sql"));
+
+                let parent_id: Option<Uuid> = query
+                    .bind(token)
+                    .fetch_optional(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?
+                    .map(|(x,)| x);
+
+                Ok(parent_id)
+            }
+            .boxed()
+        }
+
+        #[allow(clippy::type_complexity)]
+        fn lookup_key(
+            &self,
+            key: &Uuid,
+        ) -> BoxFuture<Result<Option<(Uuid, Vec<Uuid>)>, BackendError>> {
+            let key = *key;
+
+            async move {
+                let query = sqlx::query_as(include_str!("queries/lookup_key.sql"));
+
+                let found: Option<(Uuid, Vec<Uuid>)> = query
+                    .bind(key)
+                    .fetch_optional(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(found)
+            }
+            .boxed()
+        }
+
+        fn move_object(
+            &self,
+            id: &Uuid,
+            from: &str,
+            to: &str,
+            url: Option<&Url>,
+        ) -> BoxFuture<Result<(), BackendError>> {
+            let id = *id;
+            let from = from.to_owned();
+            let to = to.to_owned();
+            let url = url.map(Url::to_string);
+
+            async move {
+                let query = sqlx::query(include_str!("queries/move_object.sql"));
+
+                let result = query
+                    .bind(id)
+                    .bind(from)
+                    .bind(to)
+                    .bind(url)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentId(id))
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+
+        fn reorder_labels(&self, kind: Kind, ids: Vec<Id>) -> BoxFuture<Result<(), BackendError>> {
+            async move {
+                let sql = label_query(include_str!("queries/reorder_label.sql"), kind);
+                let mut transaction = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+                for (position, id) in ids.into_iter().enumerate() {
+                    let result = sqlx::query(&sql)
+                        .bind(id)
+                        .bind((position + 1) as i16)
+                        .execute(&mut transaction)
+                        .await
+                        .map_err(map_sqlx_error)?;
+
+                    // dropping the transaction rolls it back
+                    if result.rows_affected() == 0 {
+                        return Err(BackendError::NonExistentLabel { kind, id });
+                    }
+                }
+
+                transaction.commit().await.map_err(map_sqlx_error)?;
+
+                Ok(())
+            }
+            .boxed()
+        }
+
+        fn purge(&self, id: &Uuid) -> BoxFuture<Result<(), BackendError>> {
+            let id = *id;
+
+            async move {
+                let mut transaction = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+                sqlx::query(include_str!("queries/delete_key.sql"))
+                    .bind(id)
+                    .execute(&mut transaction)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                sqlx::query(include_str!("queries/delete_recording_tokens.sql"))
+                    .bind(id)
+                    .execute(&mut transaction)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                let result = sqlx::query(include_str!("queries/purge.sql"))
+                    .bind(id)
+                    .execute(&mut transaction)
+                    .await
+                    .map_err(|error| match error {
+                        sqlx::Error::Database(ref e)
+                            if e.constraint() == Some(RECORDINGS_PARENT_CONSTRAINT) =>
+                        {
+                            BackendError::RecordingHasChildren(id)
+                        }
+                        _ => map_sqlx_error(error),
+                    })?;
+
+                // dropping the transaction rolls it back
+                if result.rows_affected() == 0 {
+                    return Err(BackendError::NonExistentId(id));
+                }
+
+                transaction.commit().await.map_err(map_sqlx_error)?;
+
+                Ok(())
+            }
+            .boxed()
+        }
+
+        fn release_token(&self, token: &Uuid) -> BoxFuture<Result<(), BackendError>> {
+            let token = *token;
+
+            async move {
+                let query = sqlx::query(include_str!("queries/release_token.sql"));
 
-                let (id, created_at, updated_at): (Uuid, OffsetDateTime, OffsetDateTime) = query
-                    .bind(&DEFAULT_URL)
-                    .bind(None::<Option<i16>>)
-                    .bind(&metadata.category_id)
-                    .bind(parent_id)
-                    .bind(&metadata.name)
-                    .bind(&metadata.location)
-                    .bind(&metadata.occupation)
-                    .bind(&metadata.age_id)
-                    .bind(&metadata.gender_id)
-                    .fetch_one(&self.pool)
+                query
+                    .bind(token)
+                    .execute(&self.pool)
                     .await
                     .map_err(map_sqlx_error)?;
 
-                Ok(NewRecording::new(id, created_at, updated_at, metadata))
+                Ok(())
             }
             .boxed()
         }
 
-        fn lock_token(&self, token: &Uuid) -> BoxFuture<Result<Option<Uuid>, BackendError>> {
+        fn remove_token(&self, token: &Uuid) -> BoxFuture<Result<(), BackendError>> {
             let token = *token;
 
             async move {
-                let query = sqlx::query_as(include_str!("queries/lock_token.sql"));
+                let query = sqlx::query(include_str!("queries/remove_token.sql"));
 
-                let parent_id: Option<Uuid> = query
+                query
                     .bind(token)
-                    .fetch_optional(&self.pool)
+                    .execute(&self.pool)
                     .await
-                    .map_err(map_sqlx_error)?
-                    .map(|(x,)| x);
+                    .map_err(map_sqlx_error)?;
 
-                Ok(parent_id)
+                Ok(())
             }
             .boxed()
         }
 
-        #[allow(clippy::type_complexity)]
-        fn lookup_key(
+        fn reserve_name(
             &self,
-            key: &Uuid,
-        ) -> BoxFuture<Result<Option<(Uuid, Vec<Uuid>)>, BackendError>> {
-            let key = *key;
-
+            reservation: NewReservation,
+            duration: Duration,
+        ) -> BoxFuture<Result<Reservation, BackendError>> {
             async move {
-                let query = sqlx::query_as(include_str!("queries/lookup_key.sql"));
+                let NewReservation { name, token } = reservation;
+                let name_skeleton = skeleton(&name);
 
-                let found: Option<(Uuid, Vec<Uuid>)> = query
-                    .bind(key)
-                    .fetch_optional(&self.pool)
+                let mut transaction = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+                // expired reservations would otherwise still hold their names
+                sqlx::query(include_str!("queries/delete_expired_reservations.sql"))
+                    .execute(&mut transaction)
                     .await
                     .map_err(map_sqlx_error)?;
 
-                Ok(found)
+                // dropping the transaction on any error rolls it back
+                let (expires_at,): (OffsetDateTime,) =
+                    sqlx::query_as(include_str!("queries/reserve_name.sql"))
+                        .bind(token)
+                        .bind(&name)
+                        .bind(&name_skeleton)
+                        .bind(duration.as_secs() as i64)
+                        .fetch_optional(&mut transaction)
+                        .await
+                        .map_err(map_sqlx_error)?
+                        .ok_or(BackendError::InvalidToken { token })?;
+
+                let (taken,): (bool,) =
+                    sqlx::query_as(include_str!("queries/check_availability.sql"))
+                        .bind(&name)
+                        .bind(&name_skeleton)
+                        .bind(token)
+                        .fetch_one(&mut transaction)
+                        .await
+                        .map_err(map_sqlx_error)?;
+
+                if taken {
+                    return Err(BackendError::NameAlreadyExists);
+                }
+
+                transaction.commit().await.map_err(map_sqlx_error)?;
+
+                Ok(Reservation { name, expires_at })
             }
             .boxed()
         }
 
-        fn release_token(&self, token: &Uuid) -> BoxFuture<Result<(), BackendError>> {
-            let token = *token;
+        fn resolve_report(
+            &self,
+            id: &Uuid,
+            resolution: Resolution,
+        ) -> BoxFuture<Result<(), BackendError>> {
+            let id = *id;
 
             async move {
-                let query = sqlx::query(include_str!("queries/release_token.sql"));
+                let query = sqlx::query(include_str!("queries/resolve_report.sql"));
 
-                query
-                    .bind(token)
+                let result = query
+                    .bind(id)
+                    .bind(resolution.as_str())
                     .execute(&self.pool)
                     .await
                     .map_err(map_sqlx_error)?;
 
-                Ok(())
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentId(id))
+                } else {
+                    Ok(())
+                }
             }
             .boxed()
         }
 
-        fn remove_token(&self, token: &Uuid) -> BoxFuture<Result<(), BackendError>> {
-            let token = *token;
+        fn restore(&self, id: &Uuid) -> BoxFuture<Result<(), BackendError>> {
+            let id = *id;
 
             async move {
-                let query = sqlx::query(include_str!("queries/remove_token.sql"));
+                let query = sqlx::query(include_str!("queries/restore.sql"));
 
-                query
-                    .bind(token)
+                let result = query
+                    .bind(id)
                     .execute(&self.pool)
                     .await
                     .map_err(map_sqlx_error)?;
 
-                Ok(())
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentId(id))
+                } else {
+                    Ok(())
+                }
             }
             .boxed()
         }
@@ -403,11 +1358,14 @@ mod postgres {
             .boxed()
         }
 
-        fn retrieve_ages(&self) -> BoxFuture<Result<Vec<Label>, BackendError>> {
+        fn retrieve_ages(&self, locales: &[String]) -> BoxFuture<Result<Vec<Label>, BackendError>> {
+            let locales = locales.to_vec();
+
             async move {
                 let query = sqlx::query(include_str!("queries/retrieve_ages.sql"));
 
                 let ages: Vec<Label> = query
+                    .bind(locales)
                     .try_map(|row: PgRow| {
                         Ok(Label::new(
                             try_get(&row, "id")?,
@@ -424,11 +1382,17 @@ mod postgres {
             .boxed()
         }
 
-        fn retrieve_categories(&self) -> BoxFuture<Result<Vec<Label>, BackendError>> {
+        fn retrieve_categories(
+            &self,
+            locales: &[String],
+        ) -> BoxFuture<Result<Vec<Label>, BackendError>> {
+            let locales = locales.to_vec();
+
             async move {
                 let query = sqlx::query(include_str!("queries/retrieve_categories.sql"));
 
                 let categories: Vec<Label> = query
+                    .bind(locales)
                     .try_map(|row: PgRow| {
                         Ok(Label::new(
                             try_get(&row, "id")?,
@@ -445,6 +1409,39 @@ mod postgres {
             .boxed()
         }
 
+        fn retrieve_direct_upload(
+            &self,
+            id: &Uuid,
+        ) -> BoxFuture<Result<Option<DirectUpload>, BackendError>> {
+            let id = *id;
+
+            async move {
+                let query = sqlx::query(include_str!("queries/retrieve_direct_upload.sql"));
+
+                let upload = query
+                    .bind(id)
+                    .try_map(|row: PgRow| {
+                        let metadata: String = try_get(&row, "metadata")?;
+                        let length: i64 = try_get(&row, "length")?;
+
+                        Ok(DirectUpload {
+                            id: try_get(&row, "id")?,
+                            token: try_get(&row, "token")?,
+                            parent_id: try_get(&row, "parent_id")?,
+                            metadata: serde_json::from_str(&metadata)
+                                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                            length: length as u64,
+                        })
+                    })
+                    .fetch_optional(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(upload)
+            }
+            .boxed()
+        }
+
         #[allow(clippy::needless_question_mark)]
         fn retrieve_format_essences(&self) -> BoxFuture<Result<Vec<String>, BackendError>> {
             async move {
@@ -461,11 +1458,17 @@ mod postgres {
             .boxed()
         }
 
-        fn retrieve_genders(&self) -> BoxFuture<Result<Vec<Label>, BackendError>> {
+        fn retrieve_genders(
+            &self,
+            locales: &[String],
+        ) -> BoxFuture<Result<Vec<Label>, BackendError>> {
+            let locales = locales.to_vec();
+
             async move {
                 let query = sqlx::query(include_str!("queries/retrieve_genders.sql"));
 
                 let genders: Vec<Label> = query
+                    .bind(locales)
                     .try_map(|row: PgRow| {
                         Ok(Label::new(
                             try_get(&row, "id")?,
@@ -482,6 +1485,47 @@ mod postgres {
             .boxed()
         }
 
+        fn retrieve_blocked_terms(&self) -> BoxFuture<Result<Vec<BlockedTerm>, BackendError>> {
+            async move {
+                let query = sqlx::query_as::<_, BlockedTerm>(include_str!(
+                    "queries/retrieve_blocked_terms.sql"
+                ));
+
+                let terms = query.fetch_all(&self.pool).await.map_err(map_sqlx_error)?;
+
+                Ok(terms)
+            }
+            .boxed()
+        }
+
+        fn retrieve_formats(&self) -> BoxFuture<Result<Vec<ManagedFormat>, BackendError>> {
+            async move {
+                let query = sqlx::query_as::<_, ManagedFormat>(include_str!(
+                    "queries/retrieve_formats.sql"
+                ));
+
+                let formats = query.fetch_all(&self.pool).await.map_err(map_sqlx_error)?;
+
+                Ok(formats)
+            }
+            .boxed()
+        }
+
+        fn retrieve_labels(
+            &self,
+            kind: Kind,
+        ) -> BoxFuture<Result<Vec<ManagedLabel>, BackendError>> {
+            async move {
+                let sql = label_query(include_str!("queries/retrieve_labels.sql"), kind);
+                let query = sqlx::query_as::<_, ManagedLabel>(&sql);
+
+                let labels = query.fetch_all(&self.pool).await.map_err(map_sqlx_error)?;
+
+                Ok(labels)
+            }
+            .boxed()
+        }
+
         fn retrieve_mime_type(
             &self,
             format: &AudioFormat,
@@ -517,6 +1561,34 @@ mod postgres {
             .boxed()
         }
 
+        fn retrieve_open_reports(&self) -> BoxFuture<Result<Vec<Report>, BackendError>> {
+            async move {
+                let query = sqlx::query(include_str!("queries/retrieve_open_reports.sql"));
+
+                let reports = query
+                    .try_map(|row: PgRow| {
+                        let reason: String = try_get(&row, "reason")?;
+                        let reason: Reason = reason
+                            .parse()
+                            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+                        Ok(Report::new(
+                            try_get(&row, "id")?,
+                            try_get(&row, "recording_id")?,
+                            reason,
+                            try_get(&row, "note")?,
+                            try_get(&row, "created_at")?,
+                        ))
+                    })
+                    .fetch_all(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(reports)
+            }
+            .boxed()
+        }
+
         fn retrieve_random(
             &self,
             count: i16,
@@ -542,6 +1614,51 @@ mod postgres {
             .boxed()
         }
 
+        fn retrieve_recent(
+            &self,
+            count: i16,
+        ) -> BoxFuture<Result<Vec<ModeratedRecording>, BackendError>> {
+            async move {
+                let query = sqlx::query_as::<_, ModeratedRecording>(include_str!(
+                    "queries/retrieve_recent.sql"
+                ));
+
+                let recordings = query
+                    .bind(count)
+                    .fetch_all(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(recordings)
+            }
+            .boxed()
+        }
+
+        fn retrieve_stored_recordings(
+            &self,
+        ) -> BoxFuture<Result<Vec<StoredRecording>, BackendError>> {
+            async move {
+                let query = sqlx::query(include_str!("queries/retrieve_stored_recordings.sql"));
+
+                let recordings = query
+                    .try_map(|row: PgRow| {
+                        Ok(StoredRecording {
+                            id: try_get(&row, "id")?,
+                            created_at: try_get(&row, "created_at")?,
+                            extension: try_get(&row, "extension")?,
+                            content_type: try_get(&row, "mime_type")?,
+                            key: try_get(&row, "object_key")?,
+                        })
+                    })
+                    .fetch_all(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(recordings)
+            }
+            .boxed()
+        }
+
         fn retrieve_token(
             &self,
             token: &Uuid,
@@ -568,22 +1685,171 @@ mod postgres {
             .boxed()
         }
 
+        fn retrieve_upload_session(
+            &self,
+            id: &Uuid,
+        ) -> BoxFuture<Result<Option<UploadSession>, BackendError>> {
+            let id = *id;
+
+            async move {
+                let query = sqlx::query(include_str!("queries/retrieve_upload_session.sql"));
+
+                let session = query
+                    .bind(id)
+                    .try_map(|row: PgRow| {
+                        let metadata: String = try_get(&row, "metadata")?;
+                        let length: i64 = try_get(&row, "length")?;
+                        let received: i64 = try_get(&row, "received")?;
+
+                        Ok(UploadSession {
+                            id: try_get(&row, "id")?,
+                            token: try_get(&row, "token")?,
+                            parent_id: try_get(&row, "parent_id")?,
+                            metadata: serde_json::from_str(&metadata)
+                                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                            length: length as u64,
+                            received: received as u64,
+                        })
+                    })
+                    .fetch_optional(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(session)
+            }
+            .boxed()
+        }
+
+        fn rewind_upload_session(
+            &self,
+            id: &Uuid,
+            offset: u64,
+            length: u64,
+        ) -> BoxFuture<Result<(), BackendError>> {
+            let id = *id;
+
+            async move {
+                let query = sqlx::query(include_str!("queries/rewind_upload_session.sql"));
+
+                query
+                    .bind(id)
+                    .bind(offset as i64)
+                    .bind(length as i64)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                Ok(())
+            }
+            .boxed()
+        }
+
+        fn save_translation(
+            &self,
+            kind: Kind,
+            id: Id,
+            locale: &str,
+            translation: Translation,
+        ) -> BoxFuture<Result<(), BackendError>> {
+            let locale = locale.to_owned();
+
+            async move {
+                let sql = label_query(include_str!("queries/save_translation.sql"), kind);
+                let query = sqlx::query(&sql);
+
+                let result = query
+                    .bind(id)
+                    .bind(locale)
+                    .bind(&translation.label)
+                    .bind(&translation.description)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentLabel { kind, id })
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+
+        fn update_format(
+            &self,
+            id: Id,
+            contents: FormatContents,
+        ) -> BoxFuture<Result<(), BackendError>> {
+            async move {
+                let query = sqlx::query(include_str!("queries/update_format.sql"));
+
+                let result = query
+                    .bind(id)
+                    .bind(&contents.container)
+                    .bind(&contents.codec)
+                    .bind(&contents.extension)
+                    .bind(&contents.essence)
+                    .bind(contents.enabled)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentFormat(id))
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+
+        fn update_label(
+            &self,
+            kind: Kind,
+            id: Id,
+            contents: LabelContents,
+        ) -> BoxFuture<Result<(), BackendError>> {
+            async move {
+                let sql = label_query(include_str!("queries/update_label.sql"), kind);
+                let query = sqlx::query(&sql);
+
+                let result = query
+                    .bind(id)
+                    .bind(&contents.label)
+                    .bind(&contents.description)
+                    .bind(contents.enabled)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_sqlx_error)?;
+
+                if result.rows_affected() == 0 {
+                    Err(BackendError::NonExistentLabel { kind, id })
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+
         fn update_url(
             &self,
             id: &Uuid,
-            url: &Url,
+            key: &str,
+            url: Option<&Url>,
             mime_type: MimeType,
         ) -> BoxFuture<Result<(), BackendError>> {
             let id = *id;
-            let url = url.clone();
+            let key = key.to_owned();
+            let url = url.map(Url::to_string);
 
             async move {
                 let query = sqlx::query(include_str!("queries/update_url.sql"));
 
                 let _ = query
                     .bind(id)
-                    .bind(url.as_str())
+                    .bind(url)
                     .bind(mime_type.id)
+                    .bind(key)
                     .execute(&self.pool)
                     .await
                     .map_err(map_sqlx_error)?;
@@ -614,12 +1880,16 @@ mod postgres {
             };
 
         let name: String = try_get(&row, "name")?;
-        let url: String = try_get(&row, "url")?;
-        let url: Url = Url::parse(&url).map_err(|source| {
-            // this should never happen, since we control the URLs
-            // that go into the database, but just for completeness...
-            sqlx::Error::Decode(Box::new(BackendError::UnableToParseUrl { url, source }))
-        })?;
+        let object_key: Option<String> = try_get(&row, "object_key")?;
+        let url: Option<String> = try_get(&row, "url")?;
+        let url: Option<Url> = match url {
+            Some(url) => Some(Url::parse(&url).map_err(|source| {
+                // this should never happen, since we control the URLs
+                // that go into the database, but just for completeness...
+                sqlx::Error::Decode(Box::new(BackendError::UnableToParseUrl { url, source }))
+            })?),
+            None => None,
+        };
 
         let mime_type = Label::new(
             try_get(&row, "mime_type_id")?,
@@ -638,7 +1908,8 @@ mod postgres {
         let occupation: Option<String> = try_get(&row, "occupation")?;
 
         Ok(Recording::Active(ActiveRecording::new(
-            id, times, name, parent_id, url, mime_type, category, gender, age, location, occupation,
+            id, times, name, parent_id, object_key, url, mime_type, category, gender, age,
+            location, occupation,
         )))
     }
 
@@ -697,6 +1968,13 @@ mod postgres {
         }
     }
 
+    // table names can't be bound as parameters, so the label queries
+    // are templated instead; the names come from `Kind`, never from
+    // user input
+    fn label_query(template: &str, kind: Kind) -> String {
+        template.replace("{table}", kind.table())
+    }
+
     fn map_sqlx_error(error: sqlx::Error) -> BackendError {
         use sqlx::Error;
 
@@ -704,9 +1982,25 @@ mod postgres {
             Error::Database(ref e) if e.constraint() == Some(RECORDINGS_ID_CONSTRAINT) => {
                 BackendError::IdAlreadyExists
             }
-            Error::Database(ref e) if e.constraint() == Some(RECORDINGS_NAME_CONSTRAINT) => {
+            Error::Database(ref e)
+                if e.constraint() == Some(RECORDINGS_NAME_CONSTRAINT)
+                    || e.constraint() == Some(RECORDINGS_NAME_SKELETON_CONSTRAINT)
+                    || e.constraint() == Some(NAME_RESERVATIONS_CONSTRAINT) =>
+            {
                 BackendError::NameAlreadyExists
             }
+            Error::Database(ref e) if e.constraint() == Some(FORMATS_CONSTRAINT) => {
+                BackendError::FormatAlreadyExists
+            }
+            Error::Database(ref e) if e.constraint() == Some(BLOCKED_TERMS_CONSTRAINT) => {
+                BackendError::BlockedTermAlreadyExists
+            }
+            Error::Database(ref e)
+                if e.constraint()
+                    .map_or(false, |c| LABEL_CONSTRAINTS.contains(&c)) =>
+            {
+                BackendError::LabelAlreadyExists
+            }
             _ => BackendError::Sqlx { source: error },
         }
     }
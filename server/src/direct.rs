@@ -0,0 +1,63 @@
+//! Uploads sent by clients straight to the store, so that the audio
+//! doesn’t have to pass through the backend on its way in.
+//!
+//! An upload is started with its metadata, which locks the token and
+//! returns a signed request for the client to make against the store.
+//! Once the client has made it, finishing the upload loads the object
+//! back and puts it through the same steps as one uploaded in a single
+//! request, after which the object is deleted either way.
+//!
+//! Objects on their way in are kept under their own prefix, apart from
+//! the recordings, and are never public.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Serialize;
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use crate::recording::UploadMetadata;
+
+/// How long a client has to make the signed request if not otherwise
+/// configured.
+pub const DEFAULT_UPLOAD_URL_DURATION: Duration = Duration::from_secs(15 * 60);
+
+/// How long an upload is kept after its signed request expires before
+/// it’s abandoned and its token released, to allow for a request made
+/// at the last moment.
+pub const DIRECT_UPLOAD_GRACE: Duration = Duration::from_secs(5 * 60);
+
+/// The prefix the keys of objects uploaded this way start with.
+pub const STAGING_PREFIX: &str = "staging/";
+
+/// The key the audio of the given upload is put under.
+pub fn staging_key(id: &Uuid) -> String {
+    format!("{}{}", STAGING_PREFIX, id)
+}
+
+/// An upload waiting for the client to put the audio in the store.
+#[derive(Clone, Debug)]
+pub struct DirectUpload {
+    /// The audio is put under the matching `staging_key`.
+    pub(crate) id: Uuid,
+    pub(crate) token: Uuid,
+    pub(crate) parent_id: Uuid,
+    pub(crate) metadata: UploadMetadata,
+
+    /// The length of the whole recording, in bytes.
+    pub(crate) length: u64,
+}
+
+/// The request a client must make to put the audio in the store.
+#[derive(Debug, Serialize)]
+pub struct DirectUploadTarget {
+    pub(crate) id: Uuid,
+    pub(crate) method: &'static str,
+    pub(crate) url: Url,
+    pub(crate) headers: BTreeMap<&'static str, String>,
+
+    #[serde(with = "time::serde::timestamp")]
+    pub(crate) expires_at: OffsetDateTime,
+}
@@ -1,10 +1,15 @@
 use std::sync::Arc;
+use std::time::Duration;
 
-use log::Logger;
+use log::{o, Logger};
+use opentelemetry::Context;
 
 use crate::errors::BackendError;
+use crate::key::KeyTemplate;
+use crate::resumable::Spool;
 use crate::store::Store;
 use crate::urls::Urls;
+use crate::validation::MetadataLimits;
 use crate::{audio::format::AudioFormat, db::Db};
 
 pub type Checker = dyn Fn(&[u8]) -> Result<Vec<AudioFormat>, BackendError> + Send + Sync;
@@ -19,40 +24,117 @@ pub struct Environment<O: Clone + Send + Sync> {
     pub logger: Arc<Logger>,
     pub db: Arc<dyn Db + Send + Sync>,
     pub urls: Arc<Urls>,
+    pub keys: Arc<KeyTemplate>,
     pub store: Arc<VecStore<O>>,
     pub checker: Arc<Checker>,
+    pub spool: Arc<Spool>,
     pub config: Config,
+
+    /// The name of the route handling the request, if any.
+    pub route: &'static str,
+
+    /// The trace context of the request being handled, if any.
+    pub trace: Context,
 }
 
 impl<O: SafeStore> Environment<O> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         logger: Arc<Logger>,
         db: Arc<dyn Db + Send + Sync>,
         urls: Arc<Urls>,
+        keys: Arc<KeyTemplate>,
         store: Arc<VecStore<O>>,
         checker: Arc<Checker>,
+        spool: Arc<Spool>,
         config: Config,
     ) -> Self {
         Self {
             logger,
             db,
             urls,
+            keys,
             store,
             checker,
+            spool,
             config,
+            route: "unknown",
+            trace: Context::new(),
+        }
+    }
+
+    /// Returns a copy of the environment for handling a request to the
+    /// named route.
+    pub fn routed(self, route: &'static str) -> Self {
+        Self { route, ..self }
+    }
+
+    /// Returns a copy of the environment for handling a request
+    /// within the given trace context.
+    pub fn traced(self, trace: Context) -> Self {
+        Self { trace, ..self }
+    }
+
+    /// Returns a copy of the environment whose logger tags every line
+    /// with the ID of the request being handled.
+    pub fn identified(self, request_id: Option<String>) -> Self {
+        match request_id {
+            Some(request_id) => Self {
+                logger: Arc::new(self.logger.new(o!("request_id" => request_id))),
+                ..self
+            },
+            None => self,
         }
     }
 }
 
+/// The number of reports a single client may submit per hour if not
+/// otherwise configured.
+pub const DEFAULT_REPORTS_PER_HOUR: u16 = 10;
+
 #[derive(Clone, Copy, Debug)]
 pub struct Config {
     pub(crate) tokens_per_recording: u8,
+
+    /// How many reports a single client may submit per hour.
+    pub(crate) reports_per_hour: u16,
+
+    /// How many distinct clients must report a recording before it’s
+    /// hidden automatically, if at all.
+    pub(crate) reports_to_hide: Option<u16>,
+
+    /// Whether to identify clients by the `X-Forwarded-For` header
+    /// set by the HTTP gateway rather than the remote address.
+    pub(crate) trust_forwarded_for: bool,
+
+    /// The longest free-text metadata accepted with an upload.
+    pub(crate) metadata_limits: MetadataLimits,
+
+    /// How long a name reserved for a token is held.
+    pub(crate) reservation_duration: Duration,
+
+    /// How long a client has to put a direct upload in the store.
+    pub(crate) upload_url_duration: Duration,
 }
 
 impl Config {
-    pub fn new(tokens_per_recording: u8) -> Self {
+    pub fn new(
+        tokens_per_recording: u8,
+        reports_per_hour: u16,
+        reports_to_hide: Option<u16>,
+        trust_forwarded_for: bool,
+        metadata_limits: MetadataLimits,
+        reservation_duration: Duration,
+        upload_url_duration: Duration,
+    ) -> Self {
         Self {
             tokens_per_recording,
+            reports_per_hour,
+            reports_to_hide,
+            trust_forwarded_for,
+            metadata_limits,
+            reservation_duration,
+            upload_url_duration,
         }
     }
 }
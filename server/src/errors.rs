@@ -1,11 +1,18 @@
 use std::io;
 
 use rusoto_core::RusotoError;
-use rusoto_s3::{DeleteObjectError, PutObjectError};
+use rusoto_s3::{
+    CopyObjectError, DeleteObjectError, GetObjectError, HeadBucketError, ListObjectsV2Error,
+    PutObjectError,
+};
+use serde::Serialize;
 use thiserror::Error;
 use uuid::Uuid;
 
 use crate::audio::format;
+use crate::blocklist;
+use crate::label::{self, Kind};
+use crate::validation::FieldError;
 
 /// Enumerates high-level errors returned by this library.
 #[derive(Debug, Error)]
@@ -32,16 +39,27 @@ pub enum BackendError {
 
     /// Represents an error running `ffprobe`.
     #[error("error running `ffprobe`")]
-    FfprobeFailed(io::Error),
+    FfprobeFailed(#[source] io::Error),
 
     /// Represents an error caused by `ffprobe` returning malformed JSON.
     #[error("failed to parse JSON received from `ffprobe`: {0}")]
     MalformedFfprobeOutput(serde_json::Error),
 
+    /// Represents an error caused by a stored value that can’t be
+    /// sent in a response, such as a MIME type that isn’t a valid
+    /// header.
+    #[error("failed to build response: {0}")]
+    MalformedResponse(#[source] warp::http::Error),
+
     /// Represents an error caused by the user uploading malformed metadata.
     #[error("failed to parse uploaded metadata: {0}")]
     MalformedUploadMetadata(serde_json::Error),
 
+    /// Represents an error caused by the user uploading metadata that
+    /// parses but breaks the validation rules.
+    #[error("invalid metadata in {}", .0.iter().map(|e| e.field).collect::<Vec<_>>().join(", "))]
+    InvalidMetadata(Vec<FieldError>),
+
     /// Represents an error caused by the user uploading a media file with too many streams.
     #[error("too many streams: should be {0}, was {1}")]
     TooManyStreams(usize, usize),
@@ -56,10 +74,55 @@ pub enum BackendError {
         source: RusotoError<DeleteObjectError>,
     },
 
+    /// Represents an error returned by the remote server when
+    /// checking that the bucket is accessible.
+    #[error("failed to access bucket")]
+    StoreCheckFailed {
+        source: RusotoError<HeadBucketError>,
+    },
+
+    /// Represents an error returned by the remote server when copying
+    /// an object.
+    #[error("failed to copy object in storage")]
+    StoreCopyFailed {
+        source: RusotoError<CopyObjectError>,
+    },
+
+    /// Represents an error returned by the remote server when loading
+    /// an object.
+    #[error("failed to load object from storage")]
+    StoreLoadFailed { source: RusotoError<GetObjectError> },
+
+    /// Represents an error reading the body of an object being loaded.
+    #[error("failed to read object from storage")]
+    StoreLoadInterrupted(#[source] io::Error),
+
+    /// Represents an error returned by the remote server when listing
+    /// objects.
+    #[error("failed to list objects in storage")]
+    StoreListFailed {
+        source: RusotoError<ListObjectsV2Error>,
+    },
+
     /// Represents an error returned by the remote server when uploading.
     #[error("failed to upload object to S3")]
     UploadFailed { source: RusotoError<PutObjectError> },
 
+    /// Represents an error caused by calls to the store being turned
+    /// away after too many of them failed in a row.
+    #[error("storage is unavailable after repeated failures")]
+    StoreUnavailable,
+
+    /// Represents an error accessing a store kept on the local
+    /// filesystem.
+    #[error("failed to access local storage")]
+    LocalStoreFailed(#[source] io::Error),
+
+    /// Represents an error caused by asking for a direct upload when
+    /// the store can’t sign requests for clients.
+    #[error("direct uploads are not supported by this store")]
+    DirectUploadsUnsupported,
+
     /// Represents an error caused by an ID being reused.
     #[error("ID already exists in database")]
     IdAlreadyExists,
@@ -76,6 +139,98 @@ pub enum BackendError {
     #[error("non-existent ID: {0}")]
     NonExistentId(Uuid),
 
+    /// Represents an error caused by the user providing the ID of a
+    /// deleted recording.
+    #[error("recording was deleted: {0}")]
+    RecordingDeleted(Uuid),
+
+    /// Represents an error caused by the user providing a
+    /// non-existent token.
+    #[error("non-existent token: {0}")]
+    NonExistentToken(Uuid),
+
+    /// Represents an error caused by the user providing a
+    /// non-existent management key.
+    #[error("non-existent key: {0}")]
+    NonExistentKey(Uuid),
+
+    /// Represents an error caused by trying to purge a recording
+    /// that other recordings still follow.
+    #[error("recording has children: {0}")]
+    RecordingHasChildren(Uuid),
+
+    /// Represents an error caused by a request to the admin server
+    /// without valid credentials.
+    #[error("not authorized")]
+    NotAuthorized,
+
+    /// Represents an error caused by a client submitting too many
+    /// reports in a short time.
+    #[error("too many reports")]
+    TooManyReports,
+
+    /// Represents an error caused by the user providing a
+    /// non-existent label ID.
+    #[error("non-existent {kind} label: {id}")]
+    NonExistentLabel { kind: Kind, id: label::Id },
+
+    /// Represents an error caused by the user referring to a
+    /// non-existent translation of a label.
+    #[error("non-existent {locale} translation of {kind} label: {id}")]
+    NonExistentTranslation {
+        kind: Kind,
+        id: label::Id,
+        locale: String,
+    },
+
+    /// Represents an error caused by a label text being reused.
+    #[error("label already exists in database")]
+    LabelAlreadyExists,
+
+    /// Represents an error caused by the user providing a
+    /// non-existent audio format ID.
+    #[error("non-existent audio format: {0}")]
+    NonExistentFormat(label::Id),
+
+    /// Represents an error caused by a container & codec combination
+    /// being reused.
+    #[error("audio format already exists in database")]
+    FormatAlreadyExists,
+
+    /// Represents an error caused by the user providing a
+    /// non-existent blocked term ID.
+    #[error("non-existent blocked term: {0}")]
+    NonExistentBlockedTerm(blocklist::Id),
+
+    /// Represents an error caused by blocking the same term twice.
+    #[error("term already blocked")]
+    BlockedTermAlreadyExists,
+
+    /// Represents an error caused by the user providing the ID of a
+    /// non-existent or finished resumable upload.
+    #[error("non-existent upload: {0}")]
+    NonExistentUpload(Uuid),
+
+    /// Represents an error caused by sending a chunk of a resumable
+    /// upload at an offset other than where the upload left off.
+    #[error("upload is at offset {expected}, not {actual}")]
+    UploadOffsetMismatch { expected: u64, actual: u64 },
+
+    /// Represents an error caused by a resumable upload exceeding its
+    /// declared length, or declaring one larger than allowed.
+    #[error("upload is longer than {length} bytes")]
+    UploadTooLong { length: u64 },
+
+    /// Represents an error caused by finishing a direct upload before
+    /// the audio has been put in the store.
+    #[error("upload has not been received: {0}")]
+    UploadIncomplete(Uuid),
+
+    /// Represents an error caused by declaring a content type for a
+    /// direct upload that no enabled audio format has.
+    #[error("unsupported content type: {0}")]
+    UnsupportedContentType(String),
+
     /// Represents an error caused by not being able to parse a URL
     /// already in the database.
     #[error("unable to parse URL {url}: {source}")]
@@ -129,6 +284,185 @@ pub enum BackendError {
     SummarizedRecordingDeleteFailed { id: Uuid, parts: Vec<String> },
 }
 
+impl BackendError {
+    /// Returns the name of the variant, for use in metrics.
+    pub fn variant(&self) -> &'static str {
+        use BackendError::*;
+
+        match self {
+            Sqlx { .. } => "Sqlx",
+            BadRequest => "BadRequest",
+            FailedToGenerateUrl { .. } => "FailedToGenerateUrl",
+            PartsMissing => "PartsMissing",
+            TemporaryFileError(..) => "TemporaryFileError",
+            FfprobeFailed(..) => "FfprobeFailed",
+            MalformedFfprobeOutput(..) => "MalformedFfprobeOutput",
+            MalformedResponse(..) => "MalformedResponse",
+            MalformedUploadMetadata(..) => "MalformedUploadMetadata",
+            InvalidMetadata(..) => "InvalidMetadata",
+            TooManyStreams(..) => "TooManyStreams",
+            MalformedFormSubmission => "MalformedFormSubmission",
+            StoreDeleteFailed { .. } => "StoreDeleteFailed",
+            StoreCheckFailed { .. } => "StoreCheckFailed",
+            StoreCopyFailed { .. } => "StoreCopyFailed",
+            StoreLoadFailed { .. } => "StoreLoadFailed",
+            StoreLoadInterrupted(..) => "StoreLoadInterrupted",
+            StoreListFailed { .. } => "StoreListFailed",
+            UploadFailed { .. } => "UploadFailed",
+            StoreUnavailable => "StoreUnavailable",
+            LocalStoreFailed(..) => "LocalStoreFailed",
+            DirectUploadsUnsupported => "DirectUploadsUnsupported",
+            IdAlreadyExists => "IdAlreadyExists",
+            NameAlreadyExists => "NameAlreadyExists",
+            InvalidId(..) => "InvalidId",
+            NonExistentId(..) => "NonExistentId",
+            RecordingDeleted(..) => "RecordingDeleted",
+            NonExistentToken(..) => "NonExistentToken",
+            NonExistentKey(..) => "NonExistentKey",
+            RecordingHasChildren(..) => "RecordingHasChildren",
+            NotAuthorized => "NotAuthorized",
+            TooManyReports => "TooManyReports",
+            NonExistentLabel { .. } => "NonExistentLabel",
+            NonExistentTranslation { .. } => "NonExistentTranslation",
+            LabelAlreadyExists => "LabelAlreadyExists",
+            NonExistentFormat(..) => "NonExistentFormat",
+            FormatAlreadyExists => "FormatAlreadyExists",
+            NonExistentBlockedTerm(..) => "NonExistentBlockedTerm",
+            BlockedTermAlreadyExists => "BlockedTermAlreadyExists",
+            NonExistentUpload(..) => "NonExistentUpload",
+            UploadOffsetMismatch { .. } => "UploadOffsetMismatch",
+            UploadTooLong { .. } => "UploadTooLong",
+            UploadIncomplete(..) => "UploadIncomplete",
+            UnsupportedContentType(..) => "UnsupportedContentType",
+            UnableToParseUrl { .. } => "UnableToParseUrl",
+            InvalidAudioFormat { .. } => "InvalidAudioFormat",
+            UnrecognizedAudioFormat => "UnrecognizedAudioFormat",
+            InvalidToken { .. } => "InvalidToken",
+            TokenRollbackFailed { .. } => "TokenRollbackFailed",
+            RecordingDeleteFailed { .. } => "RecordingDeleteFailed",
+            DeleteRollbackFailed { .. } => "DeleteRollbackFailed",
+            SummarizedRecordingDeleteFailed { .. } => "SummarizedRecordingDeleteFailed",
+        }
+    }
+
+    /// Returns a stable code for the error, for clients to match on
+    /// instead of the message. Errors the client can do nothing about
+    /// share a code per subsystem.
+    pub fn code(&self) -> &'static str {
+        use BackendError::*;
+
+        match self {
+            Sqlx { .. }
+            | TokenRollbackFailed { .. }
+            | RecordingDeleteFailed { .. }
+            | DeleteRollbackFailed { .. }
+            | SummarizedRecordingDeleteFailed { .. } => "database_error",
+            StoreDeleteFailed { .. }
+            | StoreCheckFailed { .. }
+            | StoreCopyFailed { .. }
+            | StoreLoadFailed { .. }
+            | StoreLoadInterrupted(..)
+            | StoreListFailed { .. }
+            | UploadFailed { .. }
+            | StoreUnavailable
+            | LocalStoreFailed(..) => "storage_error",
+            FailedToGenerateUrl { .. }
+            | TemporaryFileError(..)
+            | FfprobeFailed(..)
+            | MalformedFfprobeOutput(..)
+            | MalformedResponse(..)
+            | IdAlreadyExists
+            | UnableToParseUrl { .. } => "internal_error",
+            BadRequest => "bad_request",
+            PartsMissing => "parts_missing",
+            MalformedUploadMetadata(..) => "malformed_metadata",
+            InvalidMetadata(..) => "invalid_metadata",
+            TooManyStreams(..) => "too_many_streams",
+            MalformedFormSubmission => "malformed_form",
+            NameAlreadyExists => "name_taken",
+            InvalidId(..) => "invalid_id",
+            NonExistentId(..) => "recording_not_found",
+            RecordingDeleted(..) => "recording_deleted",
+            NonExistentToken(..) => "token_not_found",
+            NonExistentKey(..) => "key_not_found",
+            RecordingHasChildren(..) => "recording_has_children",
+            NotAuthorized => "not_authorized",
+            TooManyReports => "too_many_reports",
+            NonExistentLabel { .. } => "label_not_found",
+            NonExistentTranslation { .. } => "translation_not_found",
+            LabelAlreadyExists => "label_taken",
+            NonExistentFormat(..) => "format_not_found",
+            FormatAlreadyExists => "format_taken",
+            NonExistentBlockedTerm(..) => "blocked_term_not_found",
+            BlockedTermAlreadyExists => "term_already_blocked",
+            NonExistentUpload(..) => "upload_not_found",
+            UploadOffsetMismatch { .. } => "upload_offset_mismatch",
+            UploadTooLong { .. } => "upload_too_long",
+            UploadIncomplete(..) => "upload_incomplete",
+            UnsupportedContentType(..) => "unsupported_content_type",
+            DirectUploadsUnsupported => "direct_uploads_unsupported",
+            InvalidAudioFormat { .. } => "unsupported_format",
+            UnrecognizedAudioFormat => "unrecognized_format",
+            InvalidToken { .. } => "invalid_token",
+        }
+    }
+
+    /// Returns what the client got wrong in more detail than the code,
+    /// if there is anything more to say.
+    pub fn details(&self) -> Option<ErrorDetails> {
+        use BackendError::*;
+
+        match self {
+            MalformedUploadMetadata(e) => Some(ErrorDetails::Metadata {
+                line: e.line(),
+                column: e.column(),
+            }),
+            InvalidMetadata(fields) => Some(ErrorDetails::Fields {
+                fields: fields.clone(),
+            }),
+            TooManyStreams(expected, actual) => Some(ErrorDetails::Streams {
+                expected: *expected,
+                actual: *actual,
+            }),
+            InvalidAudioFormat { format } => Some(ErrorDetails::Format {
+                container: format.container.clone(),
+                codec: format.codec.clone(),
+            }),
+            InvalidId(id) => Some(ErrorDetails::Id { id: id.clone() }),
+            NonExistentId(id)
+            | RecordingDeleted(id)
+            | RecordingHasChildren(id)
+            | NonExistentUpload(id)
+            | UploadIncomplete(id) => Some(ErrorDetails::Id { id: id.to_string() }),
+            InvalidToken { token } => Some(ErrorDetails::Token { token: *token }),
+            UploadOffsetMismatch { expected, actual } => Some(ErrorDetails::Offset {
+                expected: *expected,
+                actual: *actual,
+            }),
+            UploadTooLong { length } => Some(ErrorDetails::Length { length: *length }),
+            UnsupportedContentType(content_type) => Some(ErrorDetails::ContentType {
+                content_type: content_type.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Field-level details of an error, included in error responses.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ErrorDetails {
+    ContentType { content_type: String },
+    Fields { fields: Vec<FieldError> },
+    Format { container: String, codec: String },
+    Id { id: String },
+    Length { length: u64 },
+    Metadata { line: usize, column: usize },
+    Offset { expected: u64, actual: u64 },
+    Streams { expected: usize, actual: usize },
+    Token { token: Uuid },
+}
+
 pub fn summarize_delete_errors(id: Uuid, errors: Vec<BackendError>) -> BackendError {
     BackendError::SummarizedRecordingDeleteFailed {
         id,
@@ -0,0 +1,529 @@
+//! A store that keeps objects as files under a directory, such as a
+//! replica of a remote store on a mounted volume.
+//!
+//! Keys become paths relative to the root, with each `/` starting a
+//! subdirectory. Files are written under the hidden `.partial`
+//! directory first and moved into place once complete, so that a
+//! half-written object is never read.
+//!
+//! The filesystem is only touched on tokio's blocking threads, and
+//! objects are read a chunk at a time as they're sent.
+
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use bytes::Bytes;
+use futures::future::{BoxFuture, FutureExt};
+use futures::stream::{self, BoxStream, StreamExt};
+use time::OffsetDateTime;
+use url::{ParseError, Url};
+use uuid::Uuid;
+
+use crate::errors::BackendError;
+use crate::store::{
+    Fetched, GetConditions, ObjectStream, PresignedRequest, SignedUrl, Store, StoredObject,
+};
+
+const PARTIAL_DIRECTORY: &str = ".partial";
+
+/// How much of a file is read at a time when it's sent.
+const CHUNK_LENGTH: u64 = 64 * 1024;
+
+/// A store that saves its data to the local filesystem.
+#[derive(Clone)]
+pub struct FileStore {
+    root: PathBuf,
+
+    /// Where the root is served from, if anywhere.
+    base_url: Option<Url>,
+}
+
+impl FileStore {
+    /// Creates a new instance.
+    pub fn new(root: impl Into<PathBuf>, base_url: Option<Url>) -> Self {
+        Self {
+            root: root.into(),
+            base_url,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        use std::env;
+
+        use crate::config::get_variable;
+
+        Self::new(
+            get_variable("BACKEND_FILE_STORE_PATH"),
+            env::var("BACKEND_FILE_STORE_BASE_URL")
+                .ok()
+                .map(|v| Url::parse(&v).expect("parse BACKEND_FILE_STORE_BASE_URL")),
+        )
+    }
+
+    /// Gets the path of an object, refusing keys that would lead
+    /// outside the root or into hidden directories.
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        let mut path = self.root.clone();
+
+        for component in key.split('/') {
+            if component.is_empty() || component.starts_with('.') {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid key: {}", key),
+                ));
+            }
+
+            path.push(component);
+        }
+
+        Ok(path)
+    }
+
+    fn copy_file(&self, from: &str, to: &str) -> io::Result<()> {
+        let from = self.path(from)?;
+        self.write_file(to, |partial| fs::copy(&from, partial).map(|_| ()))
+    }
+
+    fn delete_file(&self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)?) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    fn read_file(&self, key: &str, conditions: &GetConditions) -> io::Result<Option<Fetched>> {
+        let path = self.path(key)?;
+
+        let metadata = match fs::metadata(&path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            result => result?,
+        };
+
+        let length = metadata.len();
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let etag = format!("\"{:x}-{:x}\"", length, modified.as_secs());
+
+        if conditions.if_none_match.as_deref() == Some(etag.as_str()) {
+            return Ok(Some(Fetched::NotModified));
+        }
+
+        let range = match conditions.range.as_deref().map(|r| parse_range(r, length)) {
+            None | Some(ByteRange::Whole) => None,
+            Some(ByteRange::Part(start, end)) => Some((start, end)),
+            Some(ByteRange::Unsatisfiable) => return Ok(Some(Fetched::RangeNotSatisfiable)),
+        };
+
+        let (start, end) = range.unwrap_or((0, length.saturating_sub(1)));
+        let content_length = if length == 0 { 0 } else { end - start + 1 };
+
+        let mut file = fs::File::open(&path)?;
+        file.seek(SeekFrom::Start(start))?;
+
+        Ok(Some(Fetched::Object(ObjectStream {
+            content_length: Some(content_length),
+            body: read_chunks(file.take(content_length)),
+            content_range: range.map(|(start, end)| format!("bytes {}-{}/{}", start, end, length)),
+            etag: Some(etag),
+        })))
+    }
+
+    fn list_files(
+        &self,
+        directory: &Path,
+        parent: &str,
+        prefix: &str,
+    ) -> io::Result<Vec<StoredObject>> {
+        let mut objects = vec![];
+
+        let entries = match fs::read_dir(directory) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(objects),
+            result => result?,
+        };
+
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            if name.starts_with('.') {
+                continue;
+            }
+
+            let key = if parent.is_empty() {
+                name
+            } else {
+                format!("{}/{}", parent, name)
+            };
+
+            let metadata = entry.metadata()?;
+
+            if metadata.is_dir() {
+                // a directory can only hold keys matching the prefix if
+                // one could start with the other
+                let directory_prefix = format!("{}/", key);
+
+                if prefix.starts_with(&directory_prefix) || directory_prefix.starts_with(prefix) {
+                    objects.extend(self.list_files(&entry.path(), &key, prefix)?);
+                }
+            } else if key.starts_with(prefix) {
+                objects.push(StoredObject {
+                    key,
+                    last_modified: metadata.modified().ok().map(OffsetDateTime::from),
+                });
+            }
+        }
+
+        Ok(objects)
+    }
+
+    fn load_file(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)?) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            result => result.map(Some),
+        }
+    }
+
+    fn save_file(&self, key: &str, raw: &[u8]) -> io::Result<()> {
+        self.write_file(key, |partial| fs::write(partial, raw))
+    }
+
+    /// Writes an object under a temporary name with the given function
+    /// and then moves it into place.
+    fn write_file(&self, key: &str, write: impl FnOnce(&Path) -> io::Result<()>) -> io::Result<()> {
+        let path = self.path(key)?;
+        let partial = self
+            .root
+            .join(PARTIAL_DIRECTORY)
+            .join(Uuid::new_v4().to_string());
+
+        fs::create_dir_all(self.root.join(PARTIAL_DIRECTORY))?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if let Err(e) = write(&partial).and_then(|_| fs::rename(&partial, &path)) {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+
+        Ok(())
+    }
+}
+
+impl Store for FileStore {
+    type Output = ();
+    type Raw = Vec<u8>;
+
+    fn check(&self) -> BoxFuture<Result<(), BackendError>> {
+        let root = self.root.clone();
+
+        blocking(move || fs::read_dir(&root).map(|_| ()))
+    }
+
+    fn copy(&self, from: &str, to: &str) -> BoxFuture<Result<(), BackendError>> {
+        let store = self.clone();
+        let from = from.to_owned();
+        let to = to.to_owned();
+
+        blocking(move || store.copy_file(&from, &to))
+    }
+
+    fn delete(&self, key: &str) -> BoxFuture<Result<(), BackendError>> {
+        let store = self.clone();
+        let key = key.to_owned();
+
+        blocking(move || store.delete_file(&key))
+    }
+
+    fn get(
+        &self,
+        key: &str,
+        conditions: GetConditions,
+    ) -> BoxFuture<Result<Option<Fetched>, BackendError>> {
+        let store = self.clone();
+        let key = key.to_owned();
+
+        blocking(move || store.read_file(&key, &conditions))
+    }
+
+    fn get_url(&self, key: &str) -> Result<Option<Url>, ParseError> {
+        self.base_url.as_ref().map(|url| url.join(key)).transpose()
+    }
+
+    fn list(&self, prefix: &str) -> BoxFuture<Result<Vec<StoredObject>, BackendError>> {
+        let store = self.clone();
+        let prefix = prefix.to_owned();
+
+        blocking(move || store.list_files(&store.root, "", &prefix))
+    }
+
+    fn load(&self, key: &str) -> BoxFuture<Result<Option<Vec<u8>>, BackendError>> {
+        let store = self.clone();
+        let key = key.to_owned();
+
+        blocking(move || store.load_file(&key))
+    }
+
+    fn presign_save(
+        &self,
+        _key: &str,
+        _content_type: &str,
+        _length: u64,
+        _expires_in: Duration,
+    ) -> Result<Option<PresignedRequest>, ParseError> {
+        Ok(None)
+    }
+
+    fn sign_url(&self, _key: &str) -> Result<Option<SignedUrl>, ParseError> {
+        Ok(None)
+    }
+
+    // the content type isn’t kept, as it’s in the database anyway
+    fn save(
+        &self,
+        key: &str,
+        _content_type: String,
+        raw: Vec<u8>,
+    ) -> BoxFuture<Result<(), BackendError>> {
+        let store = self.clone();
+        let key = key.to_owned();
+
+        blocking(move || store.save_file(&key, &raw))
+    }
+}
+
+/// Runs filesystem calls on one of tokio's blocking threads.
+pub(crate) async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> io::Result<T> + Send + 'static,
+) -> io::Result<T> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .unwrap_or_else(|e| Err(io::Error::new(io::ErrorKind::Other, e)))
+}
+
+/// Like `run_blocking`, with failures reported as `Store` errors.
+fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> io::Result<T> + Send + 'static,
+) -> BoxFuture<'static, Result<T, BackendError>> {
+    run_blocking(work)
+        .map(|result| result.map_err(BackendError::LocalStoreFailed))
+        .boxed()
+}
+
+/// Streams what's left of a file, reading one chunk at a time on a
+/// blocking thread.
+fn read_chunks(file: io::Take<fs::File>) -> BoxStream<'static, Result<Bytes, io::Error>> {
+    stream::try_unfold(file, |mut file| async move {
+        let (file, chunk) = run_blocking(move || {
+            let mut chunk = vec![];
+            file.by_ref()
+                .take(CHUNK_LENGTH)
+                .read_to_end(&mut chunk)
+                .map(|_| (file, chunk))
+        })
+        .await?;
+
+        if chunk.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some((Bytes::from(chunk), file)))
+        }
+    })
+    .boxed()
+}
+
+/// What part of an object a `Range` header asks for.
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    /// The header can’t be understood or asks for several ranges, so
+    /// the whole object is sent as if it had been left out.
+    Whole,
+
+    /// The first and last byte asked for.
+    Part(u64, u64),
+
+    Unsatisfiable,
+}
+
+fn parse_range(header: &str, length: u64) -> ByteRange {
+    let spec = match header.trim().strip_prefix("bytes=") {
+        Some(spec) if !spec.contains(',') => spec.trim(),
+        _ => return ByteRange::Whole,
+    };
+
+    let (first, last) = match spec.split_once('-') {
+        Some(bounds) => bounds,
+        None => return ByteRange::Whole,
+    };
+
+    if first.is_empty() {
+        return match last.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if length == 0 => ByteRange::Unsatisfiable,
+            Ok(suffix) => ByteRange::Part(length.saturating_sub(suffix), length - 1),
+            Err(_) => ByteRange::Whole,
+        };
+    }
+
+    let start = match first.parse::<u64>() {
+        Ok(start) => start,
+        Err(_) => return ByteRange::Whole,
+    };
+
+    if start >= length {
+        return ByteRange::Unsatisfiable;
+    }
+
+    let end = if last.is_empty() {
+        length - 1
+    } else {
+        match last.parse::<u64>() {
+            Ok(end) if end >= start => end.min(length - 1),
+            _ => return ByteRange::Whole,
+        }
+    };
+
+    ByteRange::Part(start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream::TryStreamExt;
+
+    use super::{parse_range, ByteRange, FileStore, CHUNK_LENGTH};
+    use crate::store::{Fetched, GetConditions, Store};
+
+    #[tokio::test]
+    async fn keeps_objects_under_their_keys() {
+        let root = tempfile::tempdir().unwrap();
+        let store = FileStore::new(root.path(), None);
+
+        store
+            .save("a/b/one.ogg", "audio/ogg".to_owned(), b"one".to_vec())
+            .await
+            .unwrap();
+        store
+            .save("two.ogg", "audio/ogg".to_owned(), b"two".to_vec())
+            .await
+            .unwrap();
+        store.copy("two.ogg", "a/three.ogg").await.unwrap();
+
+        assert_eq!(store.load("a/b/one.ogg").await.unwrap().unwrap(), b"one");
+        assert_eq!(store.load("a/three.ogg").await.unwrap().unwrap(), b"two");
+
+        let mut keys: Vec<_> = store
+            .list("")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|object| object.key)
+            .collect();
+        keys.sort();
+        assert_eq!(keys, vec!["a/b/one.ogg", "a/three.ogg", "two.ogg"]);
+
+        let keys: Vec<_> = store
+            .list("a/b")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|object| object.key)
+            .collect();
+        assert_eq!(keys, vec!["a/b/one.ogg"]);
+
+        store.delete("two.ogg").await.unwrap();
+        store.delete("two.ogg").await.unwrap();
+        assert!(store.load("two.ogg").await.unwrap().is_none());
+
+        assert!(store.load("../outside").await.is_err());
+        assert!(store
+            .save("a//b", "audio/ogg".to_owned(), vec![])
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn sends_ranges() {
+        let root = tempfile::tempdir().unwrap();
+        let store = FileStore::new(root.path(), None);
+
+        store
+            .save("audio.ogg", "audio/ogg".to_owned(), b"abcdef".to_vec())
+            .await
+            .unwrap();
+
+        let conditions = GetConditions {
+            range: Some("bytes=2-3".to_owned()),
+            if_none_match: None,
+        };
+
+        let etag = match store.get("audio.ogg", conditions).await.unwrap().unwrap() {
+            Fetched::Object(object) => {
+                assert_eq!(object.content_range.as_deref(), Some("bytes 2-3/6"));
+
+                let body: Vec<_> = object.body.try_collect().await.unwrap();
+                assert_eq!(body.concat(), b"cd");
+
+                object.etag.unwrap()
+            }
+            _ => panic!("expected object"),
+        };
+
+        let conditions = GetConditions {
+            range: None,
+            if_none_match: Some(etag),
+        };
+
+        assert!(matches!(
+            store.get("audio.ogg", conditions).await.unwrap(),
+            Some(Fetched::NotModified)
+        ));
+    }
+
+    #[tokio::test]
+    async fn streams_whole_objects_in_chunks() {
+        let root = tempfile::tempdir().unwrap();
+        let store = FileStore::new(root.path(), None);
+        let raw: Vec<u8> = (0..CHUNK_LENGTH * 2 + 10).map(|i| i as u8).collect();
+
+        store
+            .save("audio.ogg", "audio/ogg".to_owned(), raw.clone())
+            .await
+            .unwrap();
+
+        match store
+            .get("audio.ogg", GetConditions::default())
+            .await
+            .unwrap()
+            .unwrap()
+        {
+            Fetched::Object(object) => {
+                assert_eq!(object.content_length, Some(raw.len() as u64));
+                assert!(object.content_range.is_none());
+
+                let body: Vec<_> = object.body.try_collect().await.unwrap();
+                assert_eq!(body.len(), 3);
+                assert_eq!(body.concat(), raw);
+            }
+            _ => panic!("expected object"),
+        }
+    }
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(parse_range("bytes=0-", 10), ByteRange::Part(0, 9));
+        assert_eq!(parse_range("bytes=2-4", 10), ByteRange::Part(2, 4));
+        assert_eq!(parse_range("bytes=5-100", 10), ByteRange::Part(5, 9));
+        assert_eq!(parse_range("bytes=-3", 10), ByteRange::Part(7, 9));
+        assert_eq!(parse_range("bytes=-30", 10), ByteRange::Part(0, 9));
+        assert_eq!(parse_range("bytes=10-", 10), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-0", 10), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=4-2", 10), ByteRange::Whole);
+        assert_eq!(parse_range("bytes=0-1,4-5", 10), ByteRange::Whole);
+        assert_eq!(parse_range("lines=1-2", 10), ByteRange::Whole);
+    }
+}
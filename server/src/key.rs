@@ -0,0 +1,151 @@
+//! Where recordings are kept in the store, laid out by a template such
+//! as `recordings/{yyyy}/{mm}/{id}.{ext}`.
+//!
+//! The key a recording is saved under is kept in the database, so the
+//! template can change without losing track of older recordings; the
+//! `migrate-keys` helper moves them to the new layout.
+
+use std::env;
+
+use thiserror::Error;
+use time::{OffsetDateTime, UtcOffset};
+use uuid::Uuid;
+
+/// The template used if not otherwise configured, which is the bare ID
+/// that recordings were always saved under.
+pub const DEFAULT_KEY_TEMPLATE: &str = "{id}";
+
+const PLACEHOLDERS: [&str; 5] = ["id", "ext", "yyyy", "mm", "dd"];
+
+/// A template for the keys recordings are saved under.
+#[derive(Clone, Debug)]
+pub struct KeyTemplate {
+    template: String,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum KeyTemplateError {
+    #[error("unknown placeholder {{{0}}}")]
+    UnknownPlaceholder(String),
+
+    #[error("unclosed placeholder")]
+    UnclosedPlaceholder,
+
+    #[error("template must include {{id}} to keep keys unique")]
+    MissingId,
+}
+
+impl KeyTemplate {
+    /// Checks that the template only uses known placeholders and that
+    /// it includes the ID.
+    pub fn parse(template: impl Into<String>) -> Result<Self, KeyTemplateError> {
+        let template = template.into();
+        let mut rest = template.as_str();
+        let mut has_id = false;
+
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .ok_or(KeyTemplateError::UnclosedPlaceholder)?;
+            let name = &rest[start + 1..start + end];
+
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(KeyTemplateError::UnknownPlaceholder(name.to_owned()));
+            }
+
+            has_id |= name == "id";
+            rest = &rest[start + end + 1..];
+        }
+
+        if has_id {
+            Ok(Self { template })
+        } else {
+            Err(KeyTemplateError::MissingId)
+        }
+    }
+
+    /// Reads the template from `BACKEND_S3_KEY_TEMPLATE`, falling back
+    /// on the default.
+    pub fn from_env() -> Result<Self, KeyTemplateError> {
+        Self::parse(
+            env::var("BACKEND_S3_KEY_TEMPLATE").unwrap_or_else(|_| DEFAULT_KEY_TEMPLATE.to_owned()),
+        )
+    }
+
+    /// Gets the key for a recording, with dates in UTC.
+    pub fn render(&self, id: &Uuid, created_at: OffsetDateTime, extension: &str) -> String {
+        let created_at = created_at.to_offset(UtcOffset::UTC);
+
+        self.template
+            .replace("{id}", &id.to_string())
+            .replace("{ext}", extension)
+            .replace("{yyyy}", &format!("{:04}", created_at.year()))
+            .replace("{mm}", &format!("{:02}", created_at.month()))
+            .replace("{dd}", &format!("{:02}", created_at.day()))
+    }
+}
+
+impl Default for KeyTemplate {
+    fn default() -> Self {
+        Self {
+            template: DEFAULT_KEY_TEMPLATE.to_owned(),
+        }
+    }
+}
+
+/// A recording saved in the store, along with what its key is made of.
+#[derive(Clone, Debug)]
+pub struct StoredRecording {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub extension: String,
+    pub content_type: String,
+    pub key: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, OffsetDateTime, UtcOffset};
+    use uuid::Uuid;
+
+    use super::{KeyTemplate, KeyTemplateError};
+
+    #[test]
+    fn renders_placeholders() {
+        let id = Uuid::new_v4();
+        let created_at = Date::try_from_ymd(2026, 3, 9)
+            .unwrap()
+            .try_with_hms(23, 30, 0)
+            .unwrap()
+            .assume_offset(UtcOffset::hours(-2));
+
+        let template = KeyTemplate::parse("recordings/{yyyy}/{mm}/{dd}/{id}.{ext}").unwrap();
+
+        // dates are in UTC, where it’s already the next day
+        assert_eq!(
+            template.render(&id, created_at, "ogg"),
+            format!("recordings/2026/03/10/{}.ogg", id)
+        );
+
+        assert_eq!(
+            KeyTemplate::default().render(&id, OffsetDateTime::now_utc(), "ogg"),
+            id.to_string()
+        );
+    }
+
+    #[test]
+    fn rejects_bad_templates() {
+        assert_eq!(
+            KeyTemplate::parse("{yyyy}/{name}.{ext}").unwrap_err(),
+            KeyTemplateError::UnknownPlaceholder("name".to_owned())
+        );
+        assert_eq!(
+            KeyTemplate::parse("{id").unwrap_err(),
+            KeyTemplateError::UnclosedPlaceholder
+        );
+        assert_eq!(
+            KeyTemplate::parse("{yyyy}/{mm}.{ext}").unwrap_err(),
+            KeyTemplateError::MissingId
+        );
+    }
+}
@@ -1,4 +1,8 @@
+use std::fmt;
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize, Serializer};
+use thiserror::Error;
 
 /// A label for a choice. The meaning is derived from configuration at
 /// runtime.
@@ -36,3 +40,80 @@ impl Serialize for Label {
         state.end()
     }
 }
+
+/// The kinds of label that can be managed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Age,
+    Category,
+    Gender,
+}
+
+impl Kind {
+    /// Returns the name of the table holding labels of this kind.
+    pub(crate) fn table(&self) -> &'static str {
+        match self {
+            Kind::Age => "ages",
+            Kind::Category => "categories",
+            Kind::Gender => "genders",
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.table())
+    }
+}
+
+impl FromStr for Kind {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ages" => Ok(Kind::Age),
+            "categories" => Ok(Kind::Category),
+            "genders" => Ok(Kind::Gender),
+            _ => Err(ParseError),
+        }
+    }
+}
+
+/// A label along with the details needed to manage it.
+#[derive(Clone, Debug, Serialize, sqlx::FromRow)]
+pub struct ManagedLabel {
+    id: Id,
+    label: String,
+    description: Option<String>,
+    enabled: bool,
+    sort_order: i16,
+}
+
+/// The editable contents of a label.
+#[derive(Clone, Debug, Deserialize)]
+pub struct LabelContents {
+    pub(crate) label: String,
+
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+
+    #[serde(default = "enabled_by_default")]
+    pub(crate) enabled: bool,
+}
+
+/// The translation of a label into a particular locale.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Translation {
+    pub(crate) label: String,
+
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Error)]
+#[error("unknown label kind")]
+pub struct ParseError;
@@ -1,13 +1,28 @@
 pub mod audio;
+pub mod blocklist;
 pub mod config;
 pub mod db;
+pub mod direct;
 pub mod environment;
 pub mod errors;
+pub mod filesystem;
 pub mod io;
+pub mod key;
 pub mod label;
+pub mod locale;
+pub mod metrics;
 pub mod mime_type;
 pub mod normalization;
 pub mod recording;
+pub mod replication;
+pub mod report;
+pub mod request_id;
+pub mod reservation;
+pub mod resumable;
+pub mod retry;
 pub mod routes;
 pub mod store;
+pub mod suggestions;
+pub mod telemetry;
 pub mod urls;
+pub mod validation;
@@ -0,0 +1,90 @@
+use std::cmp::Ordering;
+
+/// Lists the locales a client prefers, most preferred first, given
+/// an explicit `lang` parameter (which always wins) and the contents
+/// of an `Accept-Language` header. Tags are lowercased, and each
+/// regional variant is followed by its base language so that, say,
+/// `fr-CA` can fall back to `fr`.
+///
+/// ```
+/// use backend::locale::preferred_locales;
+/// assert_eq!(
+///     preferred_locales(None, Some("hi-IN, en;q=0.5, ur;q=0.8")),
+///     vec!["hi-in", "hi", "ur", "en"]
+/// );
+/// ```
+pub fn preferred_locales(lang: Option<&str>, accept_language: Option<&str>) -> Vec<String> {
+    let explicit = lang
+        .into_iter()
+        .flat_map(|l| l.split(','))
+        .map(|tag| (normalize_tag(tag), f32::INFINITY));
+
+    let mut weighted = explicit
+        .chain(accept_language.into_iter().flat_map(parse_accept_language))
+        .filter(|(tag, quality)| !tag.is_empty() && tag != "*" && *quality > 0.0)
+        .collect::<Vec<_>>();
+
+    // the sort is stable, so tags with equal weights keep their order
+    weighted.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+    let mut locales: Vec<String> = vec![];
+
+    for (tag, _) in weighted {
+        let base = tag.split('-').next().map(str::to_owned);
+
+        for candidate in std::iter::once(tag).chain(base) {
+            if !locales.contains(&candidate) {
+                locales.push(candidate);
+            }
+        }
+    }
+
+    locales
+}
+
+/// Lowercases and trims a language tag.
+pub fn normalize_tag(tag: impl AsRef<str>) -> String {
+    tag.as_ref().trim().to_lowercase()
+}
+
+fn parse_accept_language(header: &str) -> impl Iterator<Item = (String, f32)> + '_ {
+    header.split(',').map(|entry| {
+        let mut parts = entry.split(';');
+        let tag = normalize_tag(parts.next().unwrap_or(""));
+
+        let quality = parts
+            .find_map(|p| p.trim().strip_prefix("q="))
+            .and_then(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        (tag, quality)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::preferred_locales;
+
+    #[test]
+    fn explicit_language_wins() {
+        assert_eq!(
+            preferred_locales(Some("FR"), Some("ur, hi;q=0.9")),
+            vec!["fr", "ur", "hi"]
+        );
+    }
+
+    #[test]
+    fn ignores_wildcards_and_rejected_languages() {
+        assert_eq!(
+            preferred_locales(None, Some("*, de;q=0, en-GB;q=0.3")),
+            vec!["en-gb", "en"]
+        );
+    }
+
+    #[test]
+    fn tolerates_missing_and_malformed_input() {
+        assert!(preferred_locales(None, None).is_empty());
+        assert!(preferred_locales(Some(""), Some(" , ;q=1")).is_empty());
+        assert_eq!(preferred_locales(None, Some("hi;q=abc")), vec!["hi"]);
+    }
+}
@@ -1,20 +1,34 @@
 use std::env;
 use std::error::Error;
 use std::fs;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use futures::future::{BoxFuture, FutureExt};
 use tokio::sync::mpsc;
 use warp::Filter;
 
 use backend::audio;
-use backend::config::{get_ffprobe, get_variable};
-use backend::db::PgDb;
-use backend::environment::{Config, Environment};
+use backend::config::{get_ffprobe, get_metadata_limits, get_variable};
+use backend::db::{Db, PgDb};
+use backend::direct::{staging_key, DEFAULT_UPLOAD_URL_DURATION, DIRECT_UPLOAD_GRACE};
+use backend::environment::{Config, Environment, DEFAULT_REPORTS_PER_HOUR};
+use backend::key::KeyTemplate;
+use backend::metrics;
+use backend::replication::ReplicatedStore;
+use backend::request_id;
+use backend::reservation::DEFAULT_RESERVATION_DURATION;
+use backend::resumable::{Spool, STALE_UPLOAD_AFTER};
 use backend::routes;
-use backend::store::S3Store;
+use backend::store::Store;
+use backend::telemetry;
 use backend::urls::Urls;
-use log::{info, initialize_logger, Logger};
+use log::{debug, error, info, initialize_logger, warn, Logger};
+
+/// How often expired name reservations and abandoned uploads are
+/// removed.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -22,7 +36,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let logger = initialize_logger();
 
-    let store = Arc::new(S3Store::from_env().expect("initialize S3 store from environment"));
+    // tracing is no reason not to serve requests, so without an
+    // exporter spans are just discarded
+    if let Err(e) = telemetry::initialize(env::var("BACKEND_OTLP_ENDPOINT").ok()) {
+        warn!(logger, "Failed to set up span exporter, continuing without one"; "error" => %e);
+    }
+
+    let store = Arc::new(ReplicatedStore::from_env(logger.clone()));
 
     fs::create_dir_all(env::temp_dir()).expect("ensure temporary directory exists");
 
@@ -33,30 +53,92 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .parse()
         .expect("parse BACKEND_ADMIN_PORT as u16");
 
+    // the admin server used to be open to anyone who could reach its
+    // port, so deployments from before that have to be given a token
+    let admin_token = match env::var("BACKEND_ADMIN_TOKEN") {
+        Ok(token) if !token.trim().is_empty() => token,
+        _ => {
+            error!(
+                logger,
+                "BACKEND_ADMIN_TOKEN must be set to a secret, which admin requests then send as `Authorization: Bearer <token>`"
+            );
+            return Err("BACKEND_ADMIN_TOKEN is not set".into());
+        }
+    };
+
     info!(logger, "Starting..."; "main_port" => main_port, "admin_port" => admin_port);
     let logger = Arc::new(logger);
 
     let ffprobe_path = get_ffprobe(env::var("BACKEND_FFPROBE_PATH").ok());
-    let checker = Arc::new(audio::make_wrapper(logger.clone(), ffprobe_path));
+    let checker = Arc::new(audio::make_wrapper(logger.clone(), ffprobe_path.clone()));
 
     info!(logger, "Creating database pool...");
     let connection_string = get_variable("BACKEND_DB_CONNECTION_STRING");
     let pool = sqlx::Pool::connect(&connection_string)
         .await
         .expect("create database pool from BACKEND_DB_CONNECTION_STRING");
+    metrics::register_pool(pool.clone());
     let db = Arc::new(PgDb::new(pool));
 
+    // chunks of resumable uploads must be sent to the same instance
+    // unless this is shared storage
+    let spool = Arc::new(Spool::new(
+        env::var("BACKEND_SPOOL_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| env::temp_dir().join("uploads")),
+    ));
+
     let urls = Arc::new(Urls::new(
         get_variable("BACKEND_BASE_URL"),
         get_variable("BACKEND_RECORDINGS_PATH"),
     ));
 
+    let upload_url_duration = env::var("BACKEND_UPLOAD_URL_SECONDS")
+        .ok()
+        .map(|v| Duration::from_secs(v.parse().expect("parse BACKEND_UPLOAD_URL_SECONDS as u64")))
+        .unwrap_or(DEFAULT_UPLOAD_URL_DURATION);
+
+    tokio::spawn(clean_up(
+        logger.clone(),
+        db.clone(),
+        store.clone(),
+        spool.clone(),
+        upload_url_duration + DIRECT_UPLOAD_GRACE,
+    ));
+
     let config = Config::new(
         get_variable("BACKEND_TOKENS_PER_RECORDING")
             .parse()
             .expect("parse BACKEND_TOKENS_PER_RECORDING as u8"),
+        env::var("BACKEND_REPORTS_PER_HOUR")
+            .ok()
+            .map(|v| v.parse().expect("parse BACKEND_REPORTS_PER_HOUR as u16"))
+            .unwrap_or(DEFAULT_REPORTS_PER_HOUR),
+        env::var("BACKEND_REPORTS_TO_HIDE")
+            .ok()
+            .map(|v| v.parse().expect("parse BACKEND_REPORTS_TO_HIDE as u16")),
+        env::var("BACKEND_TRUST_FORWARDED_FOR").map_or(false, |v| v == "1"),
+        get_metadata_limits(),
+        env::var("BACKEND_RESERVATION_SECONDS")
+            .ok()
+            .map(|v| {
+                Duration::from_secs(v.parse().expect("parse BACKEND_RESERVATION_SECONDS as u64"))
+            })
+            .unwrap_or(DEFAULT_RESERVATION_DURATION),
+        upload_url_duration,
+    );
+    let keys = Arc::new(KeyTemplate::from_env().expect("parse BACKEND_S3_KEY_TEMPLATE"));
+
+    let environment = Environment::new(
+        logger.clone(),
+        db,
+        urls,
+        keys,
+        store,
+        checker,
+        spool,
+        config,
     );
-    let environment = Environment::new(logger.clone(), db, urls, store, checker, config);
 
     let (termination_sender, mut termination_receiver) = mpsc::channel::<()>(1);
 
@@ -103,6 +185,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let admin_server = start_admin_server(
         logger.clone(),
         admin_port,
+        admin_token,
+        ffprobe_path,
         environment.clone(),
         should_terminate.clone(),
         terminate.clone(),
@@ -111,10 +195,60 @@ async fn main() -> Result<(), Box<dyn Error>> {
     tokio::join!(ctrlc, main_server, admin_server);
 
     info!(logger, "Exiting gracefully...");
+    telemetry::shutdown();
 
     Ok(())
 }
 
+/// Periodically removes expired name reservations, which are ignored
+/// anyway but would otherwise pile up, and abandoned uploads, which
+/// would otherwise keep their tokens locked.
+async fn clean_up(
+    logger: Arc<Logger>,
+    db: Arc<PgDb>,
+    store: Arc<ReplicatedStore<()>>,
+    spool: Arc<Spool>,
+    direct_upload_lifetime: Duration,
+) {
+    let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match db.delete_expired_reservations().await {
+            Ok(0) => {}
+            Ok(count) => debug!(logger, "Removed expired name reservations"; "count" => count),
+            Err(e) => warn!(logger, "Failed to remove expired name reservations"; "error" => %e),
+        }
+
+        match db.delete_stale_upload_sessions(STALE_UPLOAD_AFTER).await {
+            Ok(ids) => {
+                for id in ids {
+                    debug!(logger, "Removing abandoned upload"; "upload" => %id);
+
+                    if let Err(e) = spool.remove(&id).await {
+                        warn!(logger, "Failed to remove spooled chunks"; "upload" => %id, "error" => %e);
+                    }
+                }
+            }
+            Err(e) => warn!(logger, "Failed to remove abandoned uploads"; "error" => %e),
+        }
+
+        match db.delete_stale_direct_uploads(direct_upload_lifetime).await {
+            Ok(ids) => {
+                for id in ids {
+                    debug!(logger, "Removing abandoned direct upload"; "upload" => %id);
+
+                    if let Err(e) = store.delete(&staging_key(&id)).await {
+                        warn!(logger, "Failed to delete uploaded object"; "upload" => %id, "error" => %e);
+                    }
+                }
+            }
+            Err(e) => warn!(logger, "Failed to remove abandoned direct uploads"; "error" => %e),
+        }
+    }
+}
+
 fn start_main_server<O: Clone + Send + Sync + 'static>(
     logger: Arc<Logger>,
     port: u16,
@@ -139,10 +273,18 @@ fn start_main_server<O: Clone + Send + Sync + 'static>(
         r::make_children_route(environment.clone()),
         r::make_delete_route(environment.clone()),
         r::make_retrieve_route(environment.clone()),
+        r::make_audio_route(environment.clone()),
         r::make_random_route(environment.clone()),
         r::make_token_route(environment.clone()),
         r::make_lookup_route(environment.clone()),
-        r::make_availability_route(environment),
+        r::make_report_route(environment.clone()),
+        r::make_availability_route(environment.clone()),
+        r::make_reserve_route(environment.clone()),
+        r::make_start_upload_route(environment.clone()),
+        r::make_upload_progress_route(environment.clone()),
+        r::make_upload_chunk_route(environment.clone()),
+        r::make_start_direct_upload_route(environment.clone()),
+        r::make_finalize_direct_upload_route(environment),
     ];
 
     let first = routes.pop().expect("get first route");
@@ -152,33 +294,68 @@ fn start_main_server<O: Clone + Send + Sync + 'static>(
         .fold(first, |e, r| e.or(r).unify().boxed())
         .recover(move |r| routes::format_rejection(logger2.clone(), r));
 
-    let (_, main_server) =
-        warp::serve(prefix.and(routes)).bind_with_graceful_shutdown(([0, 0, 0, 0], port), async {
-            should_terminate.await;
-        });
-
-    main_server
+    request_id::serve(warp::service(prefix.and(routes)), port, async {
+        should_terminate.await;
+    })
 }
 
 fn start_admin_server<O: Clone + Send + Sync + 'static>(
-    _logger: Arc<Logger>,
+    logger: Arc<Logger>,
     port: u16,
+    token: String,
+    ffprobe: Option<PathBuf>,
     environment: Environment<O>,
     should_terminate: futures::future::Shared<
         impl warp::Future<Output = ()> + Send + Sync + 'static,
     >,
     terminate: Arc<dyn Fn() -> BoxFuture<'static, ()> + Send + Sync + 'static>,
 ) -> impl warp::Future<Output = ()> + 'static {
+    use routes::admin as a;
+
+    let logger2 = logger.clone();
     let terminate = terminate.clone();
 
-    let routes = routes::admin::make_healthz_route(environment.clone()).or(
-        routes::admin::make_termination_route(environment, terminate),
-    );
+    let mut moderation_routes = vec![
+        a::make_recent_route(environment.clone()),
+        a::make_hide_route(environment.clone()),
+        a::make_restore_route(environment.clone()),
+        a::make_purge_route(environment.clone()),
+        a::make_reports_route(environment.clone()),
+        a::make_resolve_report_route(environment.clone()),
+        a::make_dismiss_report_route(environment.clone()),
+        a::make_labels_route(environment.clone()),
+        a::make_create_label_route(environment.clone()),
+        a::make_update_label_route(environment.clone()),
+        a::make_reorder_labels_route(environment.clone()),
+        a::make_save_translation_route(environment.clone()),
+        a::make_delete_translation_route(environment.clone()),
+        a::make_formats_route(environment.clone()),
+        a::make_create_format_route(environment.clone()),
+        a::make_update_format_route(environment.clone()),
+        a::make_delete_format_route(environment.clone()),
+        a::make_blocklist_route(environment.clone()),
+        a::make_block_term_route(environment.clone()),
+        a::make_unblock_term_route(environment.clone()),
+    ];
 
-    let (_, admin_server) =
-        warp::serve(routes).bind_with_graceful_shutdown(([0, 0, 0, 0], port), async {
-            should_terminate.await;
-        });
+    let first = moderation_routes.pop().expect("get first route");
 
-    admin_server
+    let moderation_routes = moderation_routes
+        .into_iter()
+        .fold(first, |e, r| e.or(r).unify().boxed());
+
+    // only the health checks are open to anyone who can reach the port
+    let routes = a::make_healthz_route(environment.clone())
+        .or(a::make_readyz_route(environment.clone(), ffprobe))
+        .or(a::authenticate(&token).and(
+            a::make_termination_route(environment.clone(), terminate)
+                .or(a::make_metrics_route(environment))
+                .or(moderation_routes),
+        ))
+        .recover(move |r| routes::format_rejection(logger2.clone(), r))
+        .with(a::audit(logger));
+
+    request_id::serve(warp::service(routes), port, async {
+        should_terminate.await;
+    })
 }
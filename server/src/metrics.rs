@@ -0,0 +1,164 @@
+//! Metrics exposed to Prometheus through the admin server.
+
+use lazy_static::lazy_static;
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::MetricFamily;
+use prometheus::{
+    exponential_buckets, register_histogram, register_histogram_vec, register_int_counter_vec,
+    Encoder, Histogram, HistogramVec, IntCounterVec, IntGauge, TextEncoder,
+};
+use sqlx::PgPool;
+use warp::http::StatusCode;
+
+use crate::errors::BackendError;
+
+lazy_static! {
+    static ref REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "backend_requests_total",
+        "Requests handled, by route and HTTP status.",
+        &["route", "status"]
+    )
+    .expect("register request counter");
+    static ref REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "backend_request_duration_seconds",
+        "Time taken to handle requests, by route.",
+        &["route"]
+    )
+    .expect("register request duration histogram");
+    static ref ERRORS: IntCounterVec = register_int_counter_vec!(
+        "backend_errors_total",
+        "Errors returned to clients, by variant and HTTP status.",
+        &["variant", "status"]
+    )
+    .expect("register error counter");
+    static ref UPLOAD_SIZE: Histogram = register_histogram!(
+        "backend_upload_size_bytes",
+        "Size of uploaded audio.",
+        // 16 KiB to 256 MiB
+        exponential_buckets(16.0 * 1024.0, 4.0, 8).expect("create upload size buckets")
+    )
+    .expect("register upload size histogram");
+    static ref FFPROBE_DURATION: Histogram = register_histogram!(
+        "backend_ffprobe_duration_seconds",
+        "Time taken by ffprobe to identify uploaded audio."
+    )
+    .expect("register ffprobe duration histogram");
+    static ref STORE_DURATION: HistogramVec = register_histogram_vec!(
+        "backend_store_duration_seconds",
+        "Time taken by calls to the store, by operation.",
+        &["operation"]
+    )
+    .expect("register store duration histogram");
+    static ref STORE_RETRIES: IntCounterVec = register_int_counter_vec!(
+        "backend_store_retries_total",
+        "Calls to the store retried after failing, by operation.",
+        &["operation"]
+    )
+    .expect("register store retry counter");
+}
+
+/// Counts requests to and measures the latency of the named route.
+/// The route must be wrapped after rejections are recovered, or
+/// failed requests will be missed.
+pub fn instrument(route: &'static str) -> warp::log::Log<impl Fn(warp::log::Info) + Clone + Send> {
+    warp::log::custom(move |request: warp::log::Info| {
+        REQUESTS
+            .with_label_values(&[route, request.status().as_str()])
+            .inc();
+        REQUEST_DURATION
+            .with_label_values(&[route])
+            .observe(request.elapsed().as_secs_f64());
+    })
+}
+
+/// Counts an error returned to a client.
+pub fn record_error(error: &BackendError, status: StatusCode) {
+    ERRORS
+        .with_label_values(&[error.variant(), status.as_str()])
+        .inc();
+}
+
+/// Records the size of uploaded audio.
+pub fn record_upload_size(bytes: usize) {
+    UPLOAD_SIZE.observe(bytes as f64);
+}
+
+/// Times a call to `ffprobe`.
+pub fn time_ffprobe<T>(f: impl FnOnce() -> T) -> T {
+    FFPROBE_DURATION.observe_closure_duration(f)
+}
+
+/// Starts timing a call to the store. The time is recorded when the
+/// returned timer is dropped.
+pub fn time_store(operation: &str) -> prometheus::HistogramTimer {
+    STORE_DURATION.with_label_values(&[operation]).start_timer()
+}
+
+/// Counts a call to the store that failed and is about to be retried.
+pub fn record_store_retry(operation: &str) {
+    STORE_RETRIES.with_label_values(&[operation]).inc();
+}
+
+/// Registers a collector reporting the state of the database pool.
+pub fn register_pool(pool: PgPool) {
+    prometheus::register(Box::new(PoolCollector::new(pool))).expect("register pool collector");
+}
+
+/// Renders every registered metric in the Prometheus text format.
+pub fn render() -> String {
+    let mut buffer = vec![];
+
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("encode metrics");
+
+    String::from_utf8(buffer).expect("metrics must be valid UTF-8")
+}
+
+/// The content type of the output of `render`.
+pub fn content_type() -> String {
+    TextEncoder::new().format_type().to_owned()
+}
+
+// sqlx doesn't expose any metrics of its own, so the pool is polled
+// whenever metrics are gathered
+struct PoolCollector {
+    pool: PgPool,
+    connections: IntGauge,
+    idle_connections: IntGauge,
+}
+
+impl PoolCollector {
+    fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            connections: IntGauge::new(
+                "backend_db_pool_connections",
+                "Connections currently open in the database pool.",
+            )
+            .expect("create pool size gauge"),
+            idle_connections: IntGauge::new(
+                "backend_db_pool_idle_connections",
+                "Idle connections in the database pool.",
+            )
+            .expect("create idle connections gauge"),
+        }
+    }
+}
+
+impl Collector for PoolCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        let mut descs = self.connections.desc();
+        descs.extend(self.idle_connections.desc());
+        descs
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        self.connections.set(i64::from(self.pool.size()));
+        self.idle_connections.set(self.pool.num_idle() as i64);
+
+        let mut families = self.connections.collect();
+        families.extend(self.idle_connections.collect());
+        families
+    }
+}
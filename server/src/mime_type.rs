@@ -1,4 +1,7 @@
+use serde::{Deserialize, Serialize};
+
 use crate::audio::format::AudioFormat;
+use crate::label::Id;
 
 #[derive(Clone, Debug)]
 pub struct MimeType {
@@ -18,3 +21,30 @@ impl MimeType {
         }
     }
 }
+
+/// An audio format along with the details needed to manage it.
+#[derive(Clone, Debug, Serialize, sqlx::FromRow)]
+pub struct ManagedFormat {
+    id: Id,
+    container: String,
+    codec: String,
+    extension: String,
+    essence: String,
+    enabled: bool,
+}
+
+/// The editable contents of an audio format.
+#[derive(Clone, Debug, Deserialize)]
+pub struct FormatContents {
+    pub(crate) container: String,
+    pub(crate) codec: String,
+    pub(crate) extension: String,
+    pub(crate) essence: String,
+
+    #[serde(default = "enabled_by_default")]
+    pub(crate) enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
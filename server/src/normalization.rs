@@ -13,6 +13,32 @@ pub fn normalize_name(name: impl AsRef<str>) -> String {
     name.as_ref().trim().nfd().to_string()
 }
 
+/// Reduces a name to a skeleton shared by every name that looks like
+/// it, folding case, compatibility characters and confusable
+/// lookalikes, as described in Unicode Technical Standard #39.
+///
+/// ```
+/// use backend::normalization::skeleton;
+/// assert_eq!(skeleton("Alice"), skeleton("alice"));
+/// assert_eq!(skeleton("alice"), skeleton("аlice")); // Cyrillic а
+/// assert_ne!(skeleton("alice"), skeleton("bob"));
+/// ```
+pub fn skeleton(name: impl AsRef<str>) -> String {
+    use caseless::Caseless;
+    use unicode_normalization::UnicodeNormalization;
+
+    let folded = name
+        .as_ref()
+        .trim()
+        .chars()
+        .nfkc()
+        .default_case_fold()
+        .nfkc()
+        .collect::<String>();
+
+    unicode_security::skeleton(&folded).collect()
+}
+
 /// Deserializes a `String` after running it through `normalize_name`.
 pub fn deserialize<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
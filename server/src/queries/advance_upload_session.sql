@@ -0,0 +1,7 @@
+-- nothing is updated if another chunk got there first
+UPDATE "upload_sessions"
+SET "received"   = "received" + $3,
+    "updated_at" = NOW()
+WHERE "id" = $1
+  AND "received" = $2
+RETURNING "received";
@@ -1 +1,6 @@
-SELECT EXISTS(SELECT "id" FROM "recordings" WHERE "name" = $1);
+SELECT EXISTS(SELECT "id" FROM "recordings" WHERE "name" = $1 OR "name_skeleton" = $2)
+    OR EXISTS(SELECT "token"
+              FROM "name_reservations"
+              WHERE "name_skeleton" = $2
+                AND "expires_at" > NOW()
+                AND "token" IS DISTINCT FROM $3);
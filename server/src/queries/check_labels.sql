@@ -0,0 +1,3 @@
+SELECT $1::smallint IS NULL OR EXISTS (SELECT 1 FROM "ages" WHERE "id" = $1 AND "enabled") AS "age",
+       $2::smallint IS NULL OR EXISTS (SELECT 1 FROM "genders" WHERE "id" = $2 AND "enabled") AS "gender",
+       EXISTS (SELECT 1 FROM "categories" WHERE "id" = $3 AND "enabled") AS "category";
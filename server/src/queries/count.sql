@@ -1 +1 @@
-SELECT COUNT(*) FROM "recordings" WHERE "deleted_at" IS NULL;
+SELECT COUNT(*) FROM "recordings" WHERE "deleted_at" IS NULL AND "hidden_at" IS NULL;
@@ -0,0 +1 @@
+SELECT COUNT(DISTINCT "reporter") FROM "reports" WHERE "recording_id" = $1 AND "resolved_at" IS NULL;
@@ -1,6 +1,13 @@
 -- PostgreSQL returns the SQLSTATE code 23505 in case of a unique key constraint violation.
+-- No row is returned if another token holds a reservation on the name.
 
-INSERT INTO recordings (id, url, mime_type_id, category_id, parent_id, name, location, occupation, age_id, gender_id)
-VALUES (uuid_generate_v4(), $1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING id,
-                                                                          created_at,
-                                                                          updated_at;
+INSERT INTO recordings (id, url, mime_type_id, category_id, parent_id, name, location, occupation, age_id, gender_id, name_skeleton)
+SELECT uuid_generate_v4(), $1, $2, $3, $4, $5, $6, $7, $8, $9, $10
+WHERE NOT EXISTS(SELECT "token"
+                 FROM "name_reservations"
+                 WHERE "name_skeleton" = $10
+                   AND "expires_at" > NOW()
+                   AND "token" <> $11)
+RETURNING id,
+          created_at,
+          updated_at;
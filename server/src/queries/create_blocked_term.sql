@@ -0,0 +1 @@
+INSERT INTO "blocked_terms" ("term", "whole_word") VALUES ($1, $2) RETURNING "id";
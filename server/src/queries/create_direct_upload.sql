@@ -0,0 +1,2 @@
+INSERT INTO "direct_uploads" ("id", "token", "parent_id", "metadata", "length")
+VALUES ($1, $2, $3, $4, $5);
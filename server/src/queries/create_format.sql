@@ -0,0 +1,8 @@
+WITH "mime_type" AS (
+     INSERT INTO "mime_types" ("essence") VALUES ($4)
+     ON CONFLICT ("essence") DO UPDATE SET "essence" = EXCLUDED."essence"
+     RETURNING "id"
+)
+INSERT INTO "audio_formats" ("container", "codec", "extension", "mime_type_id", "enabled")
+SELECT $1, $2, $3, "mime_type"."id", $5 FROM "mime_type"
+RETURNING "id";
@@ -0,0 +1,3 @@
+INSERT INTO "{table}" ("label", "description", "enabled", "sort_order")
+VALUES ($1, $2, $3, (SELECT COALESCE(MAX("sort_order"), 0) + 1 FROM "{table}"))
+RETURNING "id";
@@ -0,0 +1,6 @@
+INSERT INTO "reports" ("id", "recording_id", "reason", "note", "reporter")
+SELECT uuid_generate_v4(), $1, $2, $3, $4
+WHERE (SELECT COUNT(*)
+       FROM "reports"
+       WHERE "reporter" = $4 AND "created_at" > NOW() - INTERVAL '1 hour') < $5
+RETURNING "id";
@@ -0,0 +1,3 @@
+INSERT INTO "upload_sessions" ("id", "token", "parent_id", "metadata", "length")
+VALUES (uuid_generate_v4(), $1, $2, $3, $4)
+RETURNING "id";
@@ -1,2 +1,2 @@
 -- TODO under the GDPR, is it okay to store the timestamps, parent, category, and children when deleted?
-UPDATE "recordings" SET "deleted_at" = NOW(), "url" = NULL, "name" = NULL, "age_id" = NULL, "gender_id" = NULL, "location" = NULL, "occupation" = NULL WHERE "id" = $1;
+UPDATE "recordings" SET "deleted_at" = NOW(), "url" = NULL, "object_key" = NULL, "name" = NULL, "name_skeleton" = NULL, "age_id" = NULL, "gender_id" = NULL, "location" = NULL, "occupation" = NULL WHERE "id" = $1;
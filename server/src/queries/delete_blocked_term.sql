@@ -0,0 +1 @@
+DELETE FROM "blocked_terms" WHERE "id" = $1;
@@ -0,0 +1 @@
+DELETE FROM "direct_uploads" WHERE "id" = $1;
@@ -0,0 +1 @@
+DELETE FROM "name_reservations" WHERE "expires_at" <= NOW();
@@ -0,0 +1 @@
+DELETE FROM "audio_formats" WHERE "id" = $1;
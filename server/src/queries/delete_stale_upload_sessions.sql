@@ -0,0 +1,12 @@
+-- abandoned uploads give their tokens back
+WITH "stale" AS (
+    DELETE FROM "upload_sessions"
+    WHERE "updated_at" < NOW() - $1 * INTERVAL '1 second'
+    RETURNING "id", "token"
+), "released" AS (
+    UPDATE "recording_tokens"
+    SET "start" = NULL
+    FROM "stale"
+    WHERE "recording_tokens"."id" = "stale"."token"
+)
+SELECT "id" FROM "stale";
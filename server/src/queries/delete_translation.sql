@@ -0,0 +1 @@
+DELETE FROM "label_translations" WHERE "kind" = $1 AND "label_id" = $2 AND "locale" = $3;
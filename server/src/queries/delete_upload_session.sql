@@ -0,0 +1 @@
+DELETE FROM "upload_sessions" WHERE "id" = $1;
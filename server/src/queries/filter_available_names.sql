@@ -0,0 +1,11 @@
+SELECT "candidates"."name"
+FROM UNNEST($1::text[], $2::text[]) WITH ORDINALITY AS "candidates" ("name", "skeleton", "position")
+WHERE NOT EXISTS(SELECT "id"
+                 FROM "recordings"
+                 WHERE "recordings"."name" = "candidates"."name"
+                    OR "recordings"."name_skeleton" = "candidates"."skeleton")
+  AND NOT EXISTS(SELECT "token"
+                 FROM "name_reservations"
+                 WHERE "name_reservations"."name_skeleton" = "candidates"."skeleton"
+                   AND "name_reservations"."expires_at" > NOW())
+ORDER BY "candidates"."position";
@@ -0,0 +1 @@
+UPDATE "recordings" SET "hidden_at" = COALESCE("hidden_at", NOW()) WHERE "id" = $1;
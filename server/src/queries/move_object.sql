@@ -0,0 +1,5 @@
+-- nothing is updated if the recording was deleted or moved meanwhile
+UPDATE "recordings"
+SET "object_key" = $3, "url" = $4
+WHERE "id" = $1
+  AND "object_key" = $2;
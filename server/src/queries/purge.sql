@@ -0,0 +1 @@
+DELETE FROM "recordings" WHERE "id" = $1;
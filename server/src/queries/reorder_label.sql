@@ -0,0 +1 @@
+UPDATE "{table}" SET "sort_order" = $2 WHERE "id" = $1;
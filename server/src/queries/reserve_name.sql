@@ -0,0 +1,11 @@
+-- only tokens that haven't started an upload can reserve a name, and
+-- reserving another replaces the one held before
+INSERT INTO "name_reservations" ("token", "name", "name_skeleton", "expires_at")
+SELECT "id", $2, $3, NOW() + $4 * INTERVAL '1 second'
+FROM "recording_tokens"
+WHERE "id" = $1
+  AND "start" IS NULL
+ON CONFLICT ("token") DO UPDATE SET "name"          = EXCLUDED."name",
+                                    "name_skeleton" = EXCLUDED."name_skeleton",
+                                    "expires_at"    = EXCLUDED."expires_at"
+RETURNING "expires_at";
@@ -0,0 +1 @@
+UPDATE "reports" SET "resolved_at" = NOW(), "resolution" = $2 WHERE "id" = $1 AND "resolved_at" IS NULL;
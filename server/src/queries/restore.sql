@@ -0,0 +1 @@
+UPDATE "recordings" SET "hidden_at" = NULL WHERE "id" = $1;
@@ -1,5 +1,6 @@
 SELECT "recordings"."id",
        "recordings"."url",
+       "recordings"."object_key",
        "recordings"."mime_type_id",
        "recordings"."created_at",
        "recordings"."updated_at",
@@ -1 +1,15 @@
-SELECT "id", "label" FROM "ages" WHERE "enabled" IS TRUE;
+SELECT "ages"."id",
+       COALESCE("translations"."label", "ages"."label") AS "label",
+       COALESCE("translations"."description", "ages"."description") AS "description"
+FROM "ages"
+LEFT JOIN LATERAL (
+     SELECT "label_translations"."label", "label_translations"."description"
+     FROM "label_translations"
+     WHERE "label_translations"."kind" = 'ages'
+     AND "label_translations"."label_id" = "ages"."id"
+     AND "label_translations"."locale" = ANY($1)
+     ORDER BY array_position($1, "label_translations"."locale")
+     LIMIT 1
+) AS "translations" ON TRUE
+WHERE "ages"."enabled" IS TRUE
+ORDER BY "ages"."sort_order", "ages"."id";
@@ -0,0 +1 @@
+SELECT "id", "term", "whole_word" FROM "blocked_terms" ORDER BY "id";
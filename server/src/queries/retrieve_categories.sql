@@ -1 +1,15 @@
-SELECT "id", "label", "description" FROM "categories" WHERE "enabled" IS TRUE;
+SELECT "categories"."id",
+       COALESCE("translations"."label", "categories"."label") AS "label",
+       COALESCE("translations"."description", "categories"."description") AS "description"
+FROM "categories"
+LEFT JOIN LATERAL (
+     SELECT "label_translations"."label", "label_translations"."description"
+     FROM "label_translations"
+     WHERE "label_translations"."kind" = 'categories'
+     AND "label_translations"."label_id" = "categories"."id"
+     AND "label_translations"."locale" = ANY($1)
+     ORDER BY array_position($1, "label_translations"."locale")
+     LIMIT 1
+) AS "translations" ON TRUE
+WHERE "categories"."enabled" IS TRUE
+ORDER BY "categories"."sort_order", "categories"."id";
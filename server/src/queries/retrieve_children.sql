@@ -1 +1 @@
-SELECT "id", "name" FROM "recordings" WHERE "parent_id" = $1 AND "deleted_at" IS NULL ORDER BY "created_at" ASC;
+SELECT "id", "name" FROM "recordings" WHERE "parent_id" = $1 AND "deleted_at" IS NULL AND "hidden_at" IS NULL ORDER BY "created_at" ASC;
@@ -0,0 +1,3 @@
+SELECT "id", "token", "parent_id", "metadata", "length"
+FROM "direct_uploads"
+WHERE "id" = $1;
@@ -1,2 +1,8 @@
 SELECT "mime_types"."essence"
-FROM "mime_types";
+FROM "mime_types"
+WHERE EXISTS (
+      SELECT "audio_formats"."id"
+      FROM "audio_formats"
+      WHERE "audio_formats"."mime_type_id" = "mime_types"."id" AND "audio_formats"."enabled"
+)
+ORDER BY "mime_types"."id";
@@ -0,0 +1,9 @@
+SELECT "audio_formats"."id",
+       "audio_formats"."container",
+       "audio_formats"."codec",
+       "audio_formats"."extension",
+       "mime_types"."essence",
+       "audio_formats"."enabled"
+FROM "audio_formats" INNER JOIN "mime_types"
+ON "audio_formats"."mime_type_id" = "mime_types"."id"
+ORDER BY "audio_formats"."id";
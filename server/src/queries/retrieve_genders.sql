@@ -1 +1,15 @@
-SELECT "id", "label" FROM "genders" WHERE "enabled" IS TRUE;
+SELECT "genders"."id",
+       COALESCE("translations"."label", "genders"."label") AS "label",
+       COALESCE("translations"."description", "genders"."description") AS "description"
+FROM "genders"
+LEFT JOIN LATERAL (
+     SELECT "label_translations"."label", "label_translations"."description"
+     FROM "label_translations"
+     WHERE "label_translations"."kind" = 'genders'
+     AND "label_translations"."label_id" = "genders"."id"
+     AND "label_translations"."locale" = ANY($1)
+     ORDER BY array_position($1, "label_translations"."locale")
+     LIMIT 1
+) AS "translations" ON TRUE
+WHERE "genders"."enabled" IS TRUE
+ORDER BY "genders"."sort_order", "genders"."id";
@@ -0,0 +1 @@
+SELECT "id", "label", "description", "enabled", "sort_order" FROM "{table}" ORDER BY "sort_order", "id";
@@ -5,5 +5,5 @@ SELECT "mime_types"."id",
        "audio_formats"."extension"
 FROM "audio_formats" INNER JOIN "mime_types"
 ON "audio_formats"."mime_type_id" = "mime_types"."id"
-WHERE "audio_formats"."container" = $1 AND "audio_formats"."codec" = $2
+WHERE "audio_formats"."container" = $1 AND "audio_formats"."codec" = $2 AND "audio_formats"."enabled"
 LIMIT 1;
@@ -0,0 +1 @@
+SELECT "id", "recording_id", "reason", "note", "created_at" FROM "reports" WHERE "resolved_at" IS NULL ORDER BY "created_at" ASC;
@@ -2,6 +2,6 @@ SELECT "recordings"."id",
        "recordings"."name",
        "recordings"."location"
 FROM "recordings"
-WHERE "recordings"."deleted_at" IS NULL
+WHERE "recordings"."deleted_at" IS NULL AND "recordings"."hidden_at" IS NULL
 ORDER BY RANDOM()
 LIMIT $1;
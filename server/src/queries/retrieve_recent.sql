@@ -0,0 +1,9 @@
+SELECT "id",
+       "name",
+       "parent_id",
+       "created_at",
+       "deleted_at" IS NOT NULL AS "deleted",
+       "hidden_at" IS NOT NULL AS "hidden"
+FROM "recordings"
+ORDER BY "created_at" DESC
+LIMIT $1;
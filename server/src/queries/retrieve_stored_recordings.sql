@@ -0,0 +1,14 @@
+-- formats sharing a MIME type are assumed to share an extension
+SELECT "recordings"."id",
+       "recordings"."created_at",
+       "recordings"."object_key",
+       "mime_types"."essence" AS "mime_type",
+       (SELECT "audio_formats"."extension"
+        FROM "audio_formats"
+        WHERE "audio_formats"."mime_type_id" = "recordings"."mime_type_id"
+        ORDER BY "audio_formats"."id"
+        LIMIT 1) AS "extension"
+FROM "recordings"
+INNER JOIN "mime_types" ON "mime_types"."id" = "recordings"."mime_type_id"
+WHERE "recordings"."object_key" IS NOT NULL
+ORDER BY "recordings"."created_at";
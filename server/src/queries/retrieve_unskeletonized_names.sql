@@ -0,0 +1 @@
+SELECT "id", "name" FROM "recordings" WHERE "name" IS NOT NULL AND "name_skeleton" IS NULL;
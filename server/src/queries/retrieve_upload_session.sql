@@ -0,0 +1,3 @@
+SELECT "id", "token", "parent_id", "metadata", "length", "received"
+FROM "upload_sessions"
+WHERE "id" = $1;
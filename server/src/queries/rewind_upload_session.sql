@@ -0,0 +1,6 @@
+-- nothing is updated if the upload has moved on since
+UPDATE "upload_sessions"
+SET "received"   = $2,
+    "updated_at" = NOW()
+WHERE "id" = $1
+  AND "received" = $2 + $3;
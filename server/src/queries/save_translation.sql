@@ -0,0 +1,4 @@
+INSERT INTO "label_translations" ("kind", "label_id", "locale", "label", "description")
+SELECT '{table}', $1, $2, $3, $4
+WHERE EXISTS (SELECT "id" FROM "{table}" WHERE "id" = $1)
+ON CONFLICT ("kind", "label_id", "locale") DO UPDATE SET "label" = EXCLUDED."label", "description" = EXCLUDED."description";
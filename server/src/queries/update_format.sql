@@ -0,0 +1,9 @@
+WITH "mime_type" AS (
+     INSERT INTO "mime_types" ("essence") VALUES ($5)
+     ON CONFLICT ("essence") DO UPDATE SET "essence" = EXCLUDED."essence"
+     RETURNING "id"
+)
+UPDATE "audio_formats"
+SET "container" = $2, "codec" = $3, "extension" = $4, "mime_type_id" = "mime_type"."id", "enabled" = $6
+FROM "mime_type"
+WHERE "audio_formats"."id" = $1;
@@ -0,0 +1 @@
+UPDATE "{table}" SET "label" = $2, "description" = $3, "enabled" = $4 WHERE "id" = $1;
@@ -0,0 +1 @@
+UPDATE "recordings" SET "name_skeleton" = $2 WHERE "id" = $1;
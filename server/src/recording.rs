@@ -5,6 +5,7 @@ use uuid::Uuid;
 
 use crate::label::{Id, Label};
 use crate::normalization;
+use crate::store::SignedUrl;
 
 #[derive(Clone, Debug, Serialize)]
 #[serde(untagged)]
@@ -38,8 +39,16 @@ pub struct ActiveRecording {
     /// The ID of the recording.
     id: Uuid,
 
-    /// The URL of the file.
-    url: Url,
+    /// The key of the file in the store, once it has been saved.
+    #[serde(skip)]
+    object_key: Option<String>,
+
+    /// The URL of the file, which private stores only sign on request.
+    url: Option<Url>,
+
+    /// When the URL stops working, if it was signed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url_expires_at: Option<i64>,
 
     /// The MIME type of the file.
     mime_type: Label,
@@ -78,7 +87,8 @@ impl ActiveRecording {
         times: Times,
         name: String,
         parent: Option<Uuid>,
-        url: Url,
+        object_key: Option<String>,
+        url: Option<Url>,
         mime_type: Label,
         category: Label,
         gender: Option<Label>,
@@ -88,7 +98,9 @@ impl ActiveRecording {
     ) -> Self {
         ActiveRecording {
             id,
+            object_key,
             url,
+            url_expires_at: None,
             mime_type,
             times,
             category,
@@ -100,6 +112,23 @@ impl ActiveRecording {
             occupation,
         }
     }
+
+    pub fn object_key(&self) -> Option<&str> {
+        self.object_key.as_deref()
+    }
+
+    pub fn mime_type(&self) -> &Label {
+        &self.mime_type
+    }
+
+    /// Replaces the URL with one signed to work until some point.
+    pub fn with_signed_url(self, signed: SignedUrl) -> Self {
+        Self {
+            url: Some(signed.url),
+            url_expires_at: Some(signed.expires_at.unix_timestamp()),
+            ..self
+        }
+    }
 }
 
 /// A single recording deleted from the database.
@@ -167,6 +196,10 @@ impl NewRecording {
         &self.id
     }
 
+    pub fn created_at(&self) -> OffsetDateTime {
+        self.times.created_at
+    }
+
     pub fn metadata(&self) -> &UploadMetadata {
         &self.metadata
     }
@@ -217,6 +250,30 @@ pub struct ChildRecording {
     name: String,
 }
 
+/// A summary of a recording as shown to moderators.
+#[derive(Clone, Debug, Deserialize, sqlx::FromRow, Serialize)]
+pub struct ModeratedRecording {
+    /// The ID of the recording.
+    id: Uuid,
+
+    /// The name provided, if the recording hasn’t been deleted.
+    name: Option<String>,
+
+    /// The ID of the recording it follows, if any.
+    #[serde(rename = "parent")]
+    parent_id: Option<Uuid>,
+
+    /// The date and time it was created.
+    #[serde(with = "time::serde::timestamp")]
+    created_at: OffsetDateTime,
+
+    /// Whether it has been deleted by its author.
+    deleted: bool,
+
+    /// Whether it has been hidden by a moderator.
+    hidden: bool,
+}
+
 /// A single recording in the database.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Times {
@@ -0,0 +1,384 @@
+//! Stores that keep every object in more than one backend, such as S3
+//! with a copy on the local filesystem.
+//!
+//! Writes go to the primary backend first, and only its result counts;
+//! the secondaries are written to in the background, retrying with a
+//! growing delay, so that an outage of one of them never fails a
+//! request. Each secondary takes its writes one at a time, in the order
+//! they were made, so that a delete is never overtaken by the save it
+//! was meant to undo. Reads and URLs come from the primary, falling back on the
+//! secondaries for objects it doesn’t have, which keeps recordings
+//! available while a new primary is filled by the `repair-storage`
+//! helper.
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::{BoxFuture, FutureExt};
+use log::{error, warn, Logger};
+use tokio::sync::mpsc;
+use url::{ParseError, Url};
+
+use crate::environment::VecStore;
+use crate::errors::BackendError;
+use crate::filesystem::FileStore;
+use crate::store::{
+    Fetched, GetConditions, PresignedRequest, S3Store, SignedUrl, Store, StoredObject,
+};
+
+/// The backends used if not otherwise configured.
+pub const DEFAULT_STORES: &str = "s3";
+
+/// How many times a write to a secondary is tried before giving up.
+const SECONDARY_ATTEMPTS: u32 = 6;
+
+/// How long to wait before trying a failed write to a secondary again,
+/// doubled after every attempt.
+const SECONDARY_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// A store made of a primary backend and any number of secondaries.
+pub struct ReplicatedStore<O> {
+    logger: Arc<Logger>,
+    primary: Arc<VecStore<O>>,
+    secondaries: Vec<Arc<VecStore<O>>>,
+
+    /// The writes waiting to be made on each secondary.
+    queues: Vec<mpsc::UnboundedSender<(String, Replication)>>,
+}
+
+/// A write to repeat on the secondaries.
+#[derive(Clone)]
+enum Replication {
+    Copy { from: String },
+    Delete,
+    Save { content_type: String, raw: Vec<u8> },
+}
+
+impl<O: Send + 'static> ReplicatedStore<O> {
+    /// Creates a new instance, starting a task for each secondary that
+    /// makes its writes.
+    pub fn new(
+        logger: Arc<Logger>,
+        primary: Arc<VecStore<O>>,
+        secondaries: Vec<Arc<VecStore<O>>>,
+    ) -> Self {
+        let queues = secondaries
+            .iter()
+            .enumerate()
+            .map(|(index, secondary)| {
+                let logger = logger.new(log::o!("secondary" => index + 1));
+                let (sender, receiver) = mpsc::unbounded_channel();
+
+                tokio::spawn(replicate_to(logger, secondary.clone(), receiver));
+
+                sender
+            })
+            .collect();
+
+        Self {
+            logger,
+            primary,
+            secondaries,
+            queues,
+        }
+    }
+
+    /// Repeats a write on every secondary in the background.
+    fn replicate(&self, key: &str, replication: Replication) {
+        for queue in &self.queues {
+            // the task runs for as long as the store exists
+            let _ = queue.send((key.to_owned(), replication.clone()));
+        }
+    }
+}
+
+/// Makes the writes queued for a secondary in order, retrying each
+/// until it succeeds or runs out of attempts.
+async fn replicate_to<O>(
+    logger: Logger,
+    secondary: Arc<VecStore<O>>,
+    mut queue: mpsc::UnboundedReceiver<(String, Replication)>,
+) {
+    while let Some((key, replication)) = queue.recv().await {
+        let logger = logger.new(log::o!("key" => key.clone()));
+        let mut delay = SECONDARY_RETRY_DELAY;
+
+        for attempt in 1..=SECONDARY_ATTEMPTS {
+            let result = match &replication {
+                Replication::Copy { from } => secondary.copy(from, &key).await,
+                Replication::Delete => secondary.delete(&key).await,
+                Replication::Save { content_type, raw } => secondary
+                    .save(&key, content_type.clone(), raw.clone())
+                    .await
+                    .map(|_| ()),
+            };
+
+            match result {
+                Ok(()) => break,
+                Err(e) if attempt < SECONDARY_ATTEMPTS => {
+                    warn!(logger, "Failed to write to secondary store, retrying: {}", e; "attempt" => attempt);
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                Err(e) => {
+                    error!(logger, "Failed to write to secondary store, giving up: {}", e; "attempt" => attempt);
+                }
+            }
+        }
+    }
+}
+
+impl ReplicatedStore<()> {
+    /// Opens the backends named in `BACKEND_STORES`, a comma-separated
+    /// list with the primary first.
+    pub fn from_env(logger: Arc<Logger>) -> Self {
+        let mut backends = backends_from_env().into_iter().map(|(_, store)| store);
+
+        let primary = backends.next().expect("name a primary in BACKEND_STORES");
+
+        Self::new(logger, primary, backends.collect())
+    }
+}
+
+/// Opens the backends named in `BACKEND_STORES`, along with their
+/// names, with the primary first.
+pub fn backends_from_env() -> Vec<(String, Arc<VecStore<()>>)> {
+    env::var("BACKEND_STORES")
+        .unwrap_or_else(|_| DEFAULT_STORES.to_owned())
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| (name.to_owned(), open_backend(name)))
+        .collect()
+}
+
+/// Opens a backend by name, configured from its own variables.
+fn open_backend(name: &str) -> Arc<VecStore<()>> {
+    match name {
+        "s3" => Arc::new(S3Store::from_env().expect("initialize S3 store from environment")),
+        "file" => Arc::new(FileStore::from_env()),
+        _ => panic!("unknown store in BACKEND_STORES: {}", name),
+    }
+}
+
+impl<O: Send + 'static> Store for ReplicatedStore<O> {
+    type Output = O;
+    type Raw = Vec<u8>;
+
+    // a secondary being down is no reason to stop taking requests
+    fn check(&self) -> BoxFuture<Result<(), BackendError>> {
+        self.primary.check()
+    }
+
+    fn copy(&self, from: &str, to: &str) -> BoxFuture<Result<(), BackendError>> {
+        let from = from.to_owned();
+        let to = to.to_owned();
+
+        async move {
+            self.primary.copy(&from, &to).await?;
+            self.replicate(&to, Replication::Copy { from });
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn delete(&self, key: &str) -> BoxFuture<Result<(), BackendError>> {
+        let key = key.to_owned();
+
+        async move {
+            self.primary.delete(&key).await?;
+            self.replicate(&key, Replication::Delete);
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn get(
+        &self,
+        key: &str,
+        conditions: GetConditions,
+    ) -> BoxFuture<Result<Option<Fetched>, BackendError>> {
+        let key = key.to_owned();
+
+        async move {
+            match self.primary.get(&key, conditions.clone()).await {
+                Ok(Some(fetched)) => Ok(Some(fetched)),
+                primary => {
+                    for secondary in &self.secondaries {
+                        if let Ok(Some(fetched)) = secondary.get(&key, conditions.clone()).await {
+                            warn!(self.logger, "Serving object from secondary store"; "key" => &key);
+                            return Ok(Some(fetched));
+                        }
+                    }
+
+                    primary
+                }
+            }
+        }
+        .boxed()
+    }
+
+    fn get_url(&self, key: &str) -> Result<Option<Url>, ParseError> {
+        self.primary.get_url(key)
+    }
+
+    fn list(&self, prefix: &str) -> BoxFuture<Result<Vec<StoredObject>, BackendError>> {
+        self.primary.list(prefix)
+    }
+
+    fn load(&self, key: &str) -> BoxFuture<Result<Option<Vec<u8>>, BackendError>> {
+        let key = key.to_owned();
+
+        async move {
+            match self.primary.load(&key).await {
+                Ok(Some(raw)) => Ok(Some(raw)),
+                primary => {
+                    for secondary in &self.secondaries {
+                        if let Ok(Some(raw)) = secondary.load(&key).await {
+                            warn!(self.logger, "Loaded object from secondary store"; "key" => &key);
+                            return Ok(Some(raw));
+                        }
+                    }
+
+                    primary
+                }
+            }
+        }
+        .boxed()
+    }
+
+    fn presign_save(
+        &self,
+        key: &str,
+        content_type: &str,
+        length: u64,
+        expires_in: Duration,
+    ) -> Result<Option<PresignedRequest>, ParseError> {
+        self.primary
+            .presign_save(key, content_type, length, expires_in)
+    }
+
+    fn sign_url(&self, key: &str) -> Result<Option<SignedUrl>, ParseError> {
+        self.primary.sign_url(key)
+    }
+
+    fn save(
+        &self,
+        key: &str,
+        content_type: String,
+        raw: Vec<u8>,
+    ) -> BoxFuture<Result<O, BackendError>> {
+        let key = key.to_owned();
+
+        async move {
+            // the data is only cloned if there's somewhere to copy it to
+            let replication = if self.secondaries.is_empty() {
+                None
+            } else {
+                Some(Replication::Save {
+                    content_type: content_type.clone(),
+                    raw: raw.clone(),
+                })
+            };
+
+            let output = self.primary.save(&key, content_type, raw).await?;
+
+            if let Some(replication) = replication {
+                self.replicate(&key, replication);
+            }
+
+            Ok(output)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use log::initialize_logger;
+
+    use super::ReplicatedStore;
+    use crate::environment::VecStore;
+    use crate::filesystem::FileStore;
+    use crate::store::Store;
+
+    #[tokio::test]
+    async fn copies_to_secondaries_and_falls_back_on_them() {
+        let primary_root = tempfile::tempdir().unwrap();
+        let secondary_root = tempfile::tempdir().unwrap();
+        let primary = Arc::new(FileStore::new(primary_root.path(), None));
+        let secondary = Arc::new(FileStore::new(secondary_root.path(), None));
+
+        let store = ReplicatedStore::<()>::new(
+            Arc::new(initialize_logger()),
+            primary.clone(),
+            vec![secondary.clone() as Arc<VecStore<()>>],
+        );
+
+        store
+            .save("one.ogg", "audio/ogg".to_owned(), b"one".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(primary.load("one.ogg").await.unwrap().unwrap(), b"one");
+
+        // the secondary is written to in the background
+        for _ in 0..100 {
+            if secondary.load("one.ogg").await.unwrap().is_some() {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(secondary.load("one.ogg").await.unwrap().unwrap(), b"one");
+
+        secondary
+            .save("two.ogg", "audio/ogg".to_owned(), b"two".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(store.load("two.ogg").await.unwrap().unwrap(), b"two");
+        assert!(store.load("three.ogg").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn replicates_writes_in_order() {
+        let primary_root = tempfile::tempdir().unwrap();
+        let secondary_root = tempfile::tempdir().unwrap();
+        let primary = Arc::new(FileStore::new(primary_root.path(), None));
+        let secondary = Arc::new(FileStore::new(secondary_root.path(), None));
+
+        let store = ReplicatedStore::<()>::new(
+            Arc::new(initialize_logger()),
+            primary,
+            vec![secondary.clone() as Arc<VecStore<()>>],
+        );
+
+        store
+            .save("one.ogg", "audio/ogg".to_owned(), b"one".to_vec())
+            .await
+            .unwrap();
+        store.delete("one.ogg").await.unwrap();
+        store
+            .save("two.ogg", "audio/ogg".to_owned(), b"two".to_vec())
+            .await
+            .unwrap();
+
+        // once the last write has been made, so have the others
+        for _ in 0..100 {
+            if secondary.load("two.ogg").await.unwrap().is_some() {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(secondary.load("two.ogg").await.unwrap().unwrap(), b"two");
+        assert!(secondary.load("one.ogg").await.unwrap().is_none());
+    }
+}
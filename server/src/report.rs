@@ -0,0 +1,121 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::normalization;
+
+/// The reasons a listener can give for reporting a recording.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    Offensive,
+    Spam,
+    PersonalInformation,
+    Copyright,
+    Other,
+}
+
+impl Reason {
+    /// Returns the representation stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Reason::Offensive => "offensive",
+            Reason::Spam => "spam",
+            Reason::PersonalInformation => "personal_information",
+            Reason::Copyright => "copyright",
+            Reason::Other => "other",
+        }
+    }
+}
+
+impl FromStr for Reason {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "offensive" => Ok(Reason::Offensive),
+            "spam" => Ok(Reason::Spam),
+            "personal_information" => Ok(Reason::PersonalInformation),
+            "copyright" => Ok(Reason::Copyright),
+            "other" => Ok(Reason::Other),
+            _ => Err(ParseError),
+        }
+    }
+}
+
+/// The ways a moderator can close a report.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resolution {
+    /// The report was acted upon.
+    Resolved,
+
+    /// The report was rejected.
+    Dismissed,
+}
+
+impl Resolution {
+    /// Returns the representation stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resolution::Resolved => "resolved",
+            Resolution::Dismissed => "dismissed",
+        }
+    }
+}
+
+/// A report as submitted by a listener.
+#[derive(Clone, Debug, Deserialize)]
+pub struct NewReport {
+    /// The reason for the report.
+    pub(crate) reason: Reason,
+
+    /// Any further explanation provided.
+    #[serde(default)]
+    #[serde(deserialize_with = "normalization::deserialize_option")]
+    pub(crate) note: Option<String>,
+}
+
+/// An open report awaiting moderation.
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+    /// The ID of the report.
+    id: Uuid,
+
+    /// The ID of the recording reported.
+    recording_id: Uuid,
+
+    /// The reason given.
+    reason: Reason,
+
+    /// The explanation provided, if any.
+    note: Option<String>,
+
+    /// The date and time it was submitted.
+    #[serde(with = "time::serde::timestamp")]
+    created_at: OffsetDateTime,
+}
+
+impl Report {
+    pub fn new(
+        id: Uuid,
+        recording_id: Uuid,
+        reason: Reason,
+        note: Option<String>,
+        created_at: OffsetDateTime,
+    ) -> Self {
+        Self {
+            id,
+            recording_id,
+            reason,
+            note,
+            created_at,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("unknown report reason")]
+pub struct ParseError;
@@ -0,0 +1,163 @@
+//! Identifiers tying a request to its log lines and error responses.
+//!
+//! Every request is given an ID before it reaches the routes, either
+//! the one sent by the client in `X-Request-Id` or a fresh UUID. The
+//! ID is available to the routes both as that header and through
+//! [`current`], and is echoed in the response.
+//!
+//! Because the server is started here rather than by warp, the
+//! address of the peer is passed along the same way, and routes have
+//! to use [`remote`] instead of `warp::addr::remote`. So is the trace
+//! started by the client, which is only extracted once per request
+//! rather than by every route tried.
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+
+use futures::future::{poll_fn, FutureExt};
+use opentelemetry::Context;
+use uuid::Uuid;
+use warp::http::{HeaderMap, HeaderValue, Request, Response};
+use warp::hyper::server::conn::AddrStream;
+use warp::hyper::service::{make_service_fn, service_fn, Service};
+use warp::hyper::{Body, Server};
+use warp::Filter;
+
+use crate::telemetry;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// The longest request ID accepted from a client. Longer ones are
+/// replaced rather than truncated.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+    static REMOTE_ADDR: SocketAddr;
+    static TRACE: Context;
+}
+
+/// Returns the ID of the request being handled, if any.
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Returns the address of the peer that sent the request being
+/// handled, if any.
+pub fn remote_addr() -> Option<SocketAddr> {
+    REMOTE_ADDR.try_with(|addr| *addr).ok()
+}
+
+/// Returns the trace the request being handled is part of, which is
+/// empty unless the client started one.
+pub fn trace() -> Context {
+    TRACE
+        .try_with(Context::clone)
+        .unwrap_or_else(|_| Context::new())
+}
+
+/// Extracts the address of the peer that sent the request.
+pub fn remote() -> impl Filter<Extract = (Option<SocketAddr>,), Error = Infallible> + Copy {
+    warp::any().map(remote_addr)
+}
+
+/// Returns the request ID sent by the client if it’s usable, or a new
+/// one otherwise.
+pub fn accept_or_generate(headers: &HeaderMap) -> String {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_acceptable(id))
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+fn is_acceptable(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LENGTH && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Serves a service on the given port until `shutdown` completes,
+/// giving every request an ID.
+pub fn serve<S>(
+    service: S,
+    port: u16,
+    shutdown: impl Future<Output = ()>,
+) -> impl Future<Output = ()>
+where
+    S: Service<Request<Body>, Response = Response<Body>, Error = Infallible>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+{
+    let make_service = make_service_fn(move |stream: &AddrStream| {
+        let service = service.clone();
+        let remote = stream.remote_addr();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                identify(service.clone(), remote, request)
+            }))
+        }
+    });
+
+    Server::bind(&SocketAddr::from(([0, 0, 0, 0], port)))
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown)
+        .map(|result| result.expect("serve requests"))
+}
+
+async fn identify<S>(
+    mut service: S,
+    remote: SocketAddr,
+    mut request: Request<Body>,
+) -> Result<Response<Body>, Infallible>
+where
+    S: Service<Request<Body>, Response = Response<Body>, Error = Infallible>,
+{
+    let id = accept_or_generate(request.headers());
+    let trace = telemetry::extract(request.method(), request.headers());
+    let value = HeaderValue::from_str(&id).expect("request ID is a valid header value");
+
+    request
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, value.clone());
+
+    poll_fn(|cx| service.poll_ready(cx)).await?;
+    let call = TRACE.scope(trace, service.call(request));
+    let mut response = REMOTE_ADDR
+        .scope(remote, REQUEST_ID.scope(id, call))
+        .await?;
+
+    response.headers_mut().insert(REQUEST_ID_HEADER, value);
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use warp::http::HeaderMap;
+
+    use super::{accept_or_generate, REQUEST_ID_HEADER};
+
+    #[test]
+    fn accepts_reasonable_ids() {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, "abc-123".parse().unwrap());
+
+        assert_eq!(accept_or_generate(&headers), "abc-123");
+    }
+
+    #[test]
+    fn replaces_unreasonable_ids() {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, "a".repeat(200).parse().unwrap());
+
+        let id = accept_or_generate(&headers);
+        assert_ne!(id, "a".repeat(200));
+        assert_eq!(id.len(), 36);
+
+        assert_eq!(accept_or_generate(&HeaderMap::new()).len(), 36);
+    }
+}
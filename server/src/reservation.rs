@@ -0,0 +1,30 @@
+//! Names held for a short time while a recording is made, so that
+//! nobody else can take them before it’s uploaded.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::normalization;
+
+/// How long a name is held if not otherwise configured.
+pub const DEFAULT_RESERVATION_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// A request to hold a name for the recording to be made with a token.
+#[derive(Clone, Debug, Deserialize)]
+pub struct NewReservation {
+    #[serde(deserialize_with = "normalization::deserialize")]
+    pub(crate) name: String,
+    pub(crate) token: Uuid,
+}
+
+/// A name held for a token until it’s used or the reservation expires.
+#[derive(Clone, Debug, Serialize)]
+pub struct Reservation {
+    pub(crate) name: String,
+
+    #[serde(with = "time::serde::timestamp")]
+    pub(crate) expires_at: OffsetDateTime,
+}
@@ -0,0 +1,213 @@
+//! Uploads sent in chunks over several requests, so that a dropped
+//! connection only loses the chunk in flight.
+//!
+//! An upload is started with its metadata, which locks the token, and
+//! each chunk must then be sent at the offset where the last one left
+//! off. Chunks are spooled to disk until the last one arrives, when
+//! they’re put back together and the recording goes through the same
+//! steps as one uploaded in a single request.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::filesystem::run_blocking;
+use crate::recording::UploadMetadata;
+
+/// The largest chunk accepted in a single request.
+pub const MAX_CHUNK_LENGTH: u64 = 8 * 1024 * 1024;
+
+/// How long an upload may go without receiving a chunk before it’s
+/// abandoned and its token released.
+pub const STALE_UPLOAD_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// An upload in progress.
+#[derive(Clone, Debug)]
+pub struct UploadSession {
+    pub(crate) id: Uuid,
+    pub(crate) token: Uuid,
+    pub(crate) parent_id: Uuid,
+    pub(crate) metadata: UploadMetadata,
+
+    /// The length of the whole recording, in bytes.
+    pub(crate) length: u64,
+
+    /// How many bytes have been received so far, which is also the
+    /// offset the next chunk must be sent at.
+    pub(crate) received: u64,
+}
+
+/// The progress of an upload, as shown to clients.
+#[derive(Debug, Serialize)]
+pub struct UploadProgress {
+    pub(crate) id: Uuid,
+    pub(crate) offset: u64,
+    pub(crate) length: u64,
+}
+
+impl From<&UploadSession> for UploadProgress {
+    fn from(session: &UploadSession) -> Self {
+        Self {
+            id: session.id,
+            offset: session.received,
+            length: session.length,
+        }
+    }
+}
+
+/// Where the chunks of uploads in progress are kept, one directory per
+/// upload and one file per chunk, named after its offset.
+///
+/// Chunks are first written under a temporary name and only given
+/// their final one once the database has accepted them, so that the
+/// same chunk sent twice at once can’t clobber the one that counts.
+///
+/// All of the file I/O runs on tokio’s blocking threads, since a chunk
+/// can be several megabytes and an assembled upload far more.
+pub struct Spool {
+    root: PathBuf,
+}
+
+impl Spool {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Writes a chunk received at the given offset, returning the
+    /// temporary path to pass to `accept_chunk` or `discard_chunk`.
+    pub async fn write_chunk(
+        &self,
+        id: &Uuid,
+        offset: u64,
+        chunk: impl AsRef<[u8]> + Send + 'static,
+    ) -> io::Result<PathBuf> {
+        let directory = self.directory(id);
+
+        run_blocking(move || {
+            fs::create_dir_all(&directory)?;
+
+            let path = directory.join(format!("{}.{}.part", offset, Uuid::new_v4()));
+            fs::write(&path, chunk)?;
+
+            Ok(path)
+        })
+        .await
+    }
+
+    /// Gives a written chunk its final name, once the database has
+    /// recorded that it was received.
+    pub async fn accept_chunk(&self, id: &Uuid, offset: u64, path: PathBuf) -> io::Result<()> {
+        let destination = self.directory(id).join(offset.to_string());
+        run_blocking(move || fs::rename(path, destination)).await
+    }
+
+    /// Removes a written chunk that wasn’t accepted.
+    pub async fn discard_chunk(&self, path: PathBuf) -> io::Result<()> {
+        run_blocking(move || fs::remove_file(path)).await
+    }
+
+    /// Puts the accepted chunks of an upload back together, failing if
+    /// any are missing.
+    pub async fn assemble(&self, id: &Uuid, length: u64) -> io::Result<Vec<u8>> {
+        let directory = self.directory(id);
+        run_blocking(move || assemble(&directory, length)).await
+    }
+
+    /// Removes everything spooled for an upload.
+    pub async fn remove(&self, id: &Uuid) -> io::Result<()> {
+        let directory = self.directory(id);
+
+        run_blocking(move || match fs::remove_dir_all(directory) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        })
+        .await
+    }
+
+    fn directory(&self, id: &Uuid) -> PathBuf {
+        self.root.join(id.to_string())
+    }
+}
+
+fn assemble(directory: &Path, length: u64) -> io::Result<Vec<u8>> {
+    let mut offsets = vec![];
+
+    for entry in fs::read_dir(directory)? {
+        // chunks still being written have a different name
+        if let Ok(offset) = entry?.file_name().to_string_lossy().parse::<u64>() {
+            offsets.push(offset);
+        }
+    }
+
+    offsets.sort_unstable();
+
+    let mut data = Vec::with_capacity(length as usize);
+
+    for offset in offsets {
+        if offset != data.len() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("missing chunk at offset {}", data.len()),
+            ));
+        }
+
+        data.extend(fs::read(directory.join(offset.to_string()))?);
+    }
+
+    if data.len() as u64 != length {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected {} bytes, found {}", length, data.len()),
+        ));
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::Spool;
+
+    #[tokio::test]
+    async fn assembles_accepted_chunks_in_order() {
+        let root = tempfile::tempdir().unwrap();
+        let spool = Spool::new(root.path());
+        let id = Uuid::new_v4();
+
+        let second = spool.write_chunk(&id, 3, b"def").await.unwrap();
+        let first = spool.write_chunk(&id, 0, b"abc").await.unwrap();
+        let rejected = spool.write_chunk(&id, 3, b"xyz").await.unwrap();
+
+        spool.accept_chunk(&id, 3, second).await.unwrap();
+        spool.accept_chunk(&id, 0, first).await.unwrap();
+        spool.discard_chunk(rejected).await.unwrap();
+
+        assert_eq!(spool.assemble(&id, 6).await.unwrap(), b"abcdef");
+
+        spool.remove(&id).await.unwrap();
+        assert!(spool.assemble(&id, 6).await.is_err());
+        spool.remove(&id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn refuses_to_assemble_with_gaps() {
+        let root = tempfile::tempdir().unwrap();
+        let spool = Spool::new(root.path());
+        let id = Uuid::new_v4();
+
+        let chunk = spool.write_chunk(&id, 3, b"def").await.unwrap();
+        spool.accept_chunk(&id, 3, chunk).await.unwrap();
+
+        assert!(spool.assemble(&id, 6).await.is_err());
+
+        // chunks that were written but never accepted don’t count
+        spool.write_chunk(&id, 0, b"abc").await.unwrap();
+        assert!(spool.assemble(&id, 6).await.is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Keeping calls to remote services from failing on the first hiccup,
+//! or from piling up while the service is down.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How many times a failed call is retried if not otherwise configured.
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// How long to wait before the first retry if not otherwise configured.
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// How long a single attempt may take if not otherwise configured.
+/// Uploads get longer, depending on their size.
+pub const DEFAULT_CALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The slowest an upload or download may go, in bytes per second,
+/// before it's timed out if not otherwise configured.
+pub const DEFAULT_MIN_TRANSFER_RATE: u64 = 256 * 1024;
+
+/// How many calls in a row must fail before the circuit breaker opens
+/// if not otherwise configured.
+pub const DEFAULT_BREAKER_THRESHOLD: u32 = 5;
+
+/// How long the circuit breaker stays open if not otherwise configured.
+pub const DEFAULT_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// The longest wait between retries, however many there have been.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// How a failed call is retried.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// How many times to retry after the first attempt.
+    pub retries: u32,
+
+    /// How long to wait before the first retry, doubled for each one
+    /// after that.
+    pub delay: Duration,
+
+    /// How long each attempt may take.
+    pub timeout: Duration,
+
+    /// How many bytes per second an upload or download has to manage,
+    /// on top of the time allowed for any call.
+    pub min_transfer_rate: u64,
+}
+
+impl RetryPolicy {
+    /// Gets how long to wait before the given retry, counting from 0.
+    pub fn delay_before(&self, retry: u32) -> Duration {
+        self.delay
+            .checked_mul(2u32.saturating_pow(retry))
+            .map_or(MAX_RETRY_DELAY, |delay| delay.min(MAX_RETRY_DELAY))
+    }
+
+    /// Gets how long each attempt at uploading or downloading the given
+    /// number of bytes may take.
+    pub fn transfer_timeout(&self, length: u64) -> Duration {
+        let transfer = match self.min_transfer_rate {
+            0 => Duration::from_secs(0),
+            rate => Duration::from_secs(length / rate),
+        };
+
+        self.timeout + transfer
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: DEFAULT_RETRIES,
+            delay: DEFAULT_RETRY_DELAY,
+            timeout: DEFAULT_CALL_TIMEOUT,
+            min_transfer_rate: DEFAULT_MIN_TRANSFER_RATE,
+        }
+    }
+}
+
+/// Stops calls to a service after enough of them have failed in a row,
+/// letting one through now and then to find out whether it’s back.
+///
+/// Only failures that say something about the service count, so a
+/// missing object is as good as a success here.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    /// How many failures in a row open the breaker, or 0 to never open
+    /// it.
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    failures: u32,
+
+    /// When the next call may be let through, if the breaker is open.
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Checks whether a call may be made. Once the cooldown is over, a
+    /// single call is let through, and the next one has to wait for
+    /// another cooldown unless that call succeeds.
+    pub fn allow(&self) -> bool {
+        let mut state = self.state.lock().expect("lock circuit breaker");
+
+        match state.open_until {
+            None => true,
+            Some(until) if Instant::now() >= until => {
+                state.open_until = Some(Instant::now() + self.cooldown);
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// Checks whether calls are being turned away, without taking the
+    /// place of the trial call.
+    pub fn is_open(&self) -> bool {
+        let state = self.state.lock().expect("lock circuit breaker");
+
+        matches!(state.open_until, Some(until) if Instant::now() < until)
+    }
+
+    pub fn record_success(&self) {
+        *self.state.lock().expect("lock circuit breaker") = BreakerState::default();
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().expect("lock circuit breaker");
+        state.failures = state.failures.saturating_add(1);
+
+        if self.threshold > 0 && state.failures >= self.threshold {
+            state.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(DEFAULT_BREAKER_THRESHOLD, DEFAULT_BREAKER_COOLDOWN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use super::{CircuitBreaker, RetryPolicy, MAX_RETRY_DELAY};
+
+    #[test]
+    fn backs_off_exponentially() {
+        let policy = RetryPolicy {
+            retries: 10,
+            delay: Duration::from_millis(100),
+            timeout: Duration::from_secs(1),
+            min_transfer_rate: 1024,
+        };
+
+        assert_eq!(policy.delay_before(0), Duration::from_millis(100));
+        assert_eq!(policy.delay_before(1), Duration::from_millis(200));
+        assert_eq!(policy.delay_before(3), Duration::from_millis(800));
+        assert_eq!(policy.delay_before(10), MAX_RETRY_DELAY);
+        assert_eq!(policy.delay_before(40), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn gives_transfers_time_for_their_size() {
+        let policy = RetryPolicy {
+            retries: 0,
+            delay: Duration::from_millis(100),
+            timeout: Duration::from_secs(10),
+            min_transfer_rate: 1024,
+        };
+
+        assert_eq!(policy.transfer_timeout(0), Duration::from_secs(10));
+        assert_eq!(policy.transfer_timeout(10 * 1024), Duration::from_secs(20));
+
+        let unlimited = RetryPolicy {
+            min_transfer_rate: 0,
+            ..policy
+        };
+        assert_eq!(
+            unlimited.transfer_timeout(10 * 1024),
+            Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn opens_after_repeated_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(50));
+
+        breaker.record_failure();
+        assert!(breaker.allow());
+        assert!(!breaker.is_open());
+
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert!(!breaker.allow());
+
+        // a single trial call is let through after the cooldown
+        sleep(Duration::from_millis(60));
+        assert!(breaker.allow());
+        assert!(!breaker.allow());
+
+        breaker.record_success();
+        assert!(breaker.allow());
+        assert!(breaker.allow());
+    }
+
+    #[test]
+    fn stays_open_when_the_trial_fails() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+
+        breaker.record_failure();
+        sleep(Duration::from_millis(60));
+        assert!(breaker.allow());
+
+        breaker.record_failure();
+        assert!(!breaker.allow());
+    }
+
+    #[test]
+    fn never_opens_without_a_threshold() {
+        let breaker = CircuitBreaker::new(0, Duration::from_secs(60));
+
+        for _ in 0..10 {
+            breaker.record_failure();
+        }
+
+        assert!(breaker.allow());
+    }
+}
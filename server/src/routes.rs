@@ -1,11 +1,51 @@
 use std::sync::Arc;
 
 use log::{error, Logger};
+use warp::filters::BoxedFilter;
 use warp::http::StatusCode;
 use warp::reject;
-use warp::reply::{json, with_status, Json, WithStatus};
+use warp::reply::{json, with_status, Json, Reply, WithStatus};
 
 use crate::errors::BackendError;
+use crate::metrics;
+
+type Route = BoxedFilter<(Box<dyn Reply>,)>;
+
+macro_rules! route_filter {
+    ($route_variable:ident; $first:expr) => (let $route_variable = $route_variable.and($first););
+    ($route_variable:ident; $first:expr, $($rest:expr),+) => (
+        let $route_variable = $route_variable.and($first);
+        route_filter!($route_variable; $($rest),+);
+    )
+}
+
+macro_rules! route {
+    ($name:ident => $handler:ident, $route_variable:ident; $($filters:expr),+) => (
+        pub fn $name<O: SafeStore + 'static>(environment: Environment<O>) -> Route {
+            let logger = environment.logger.clone();
+            let route = crate::routes::route_name(concat!(module_path!(), "::", stringify!($handler)));
+
+            // the trace is only picked up by the handler, once the
+            // request has matched
+            let $route_variable = warp::any().map(move || {
+                environment
+                    .clone()
+                    .routed(route)
+                    .identified(crate::request_id::current())
+            });
+
+            route_filter!($route_variable; $($filters),+);
+
+            // rejections are recovered here rather than only at the
+            // top level so that the metrics see failed requests too
+            $route_variable.and_then(handlers::$handler)
+                .recover(move |r| crate::routes::format_rejection(logger.clone(), r))
+                .with(crate::metrics::instrument(route))
+                .map(|reply| Box::new(reply) as Box<dyn warp::reply::Reply>)
+                .boxed()
+        }
+    );
+}
 
 pub mod admin;
 mod handlers;
@@ -15,19 +55,35 @@ mod response;
 
 pub use internal::*;
 
+/// The prefixes of the module paths of handlers, which are stripped
+/// to name routes in metrics and traces.
+const ROUTE_PREFIX: &str = "backend::routes::";
+const PUBLIC_ROUTE_PREFIX: &str = "internal::";
+
 /// The maximum form data size to accept. This should be enforced by
 /// the HTTP gateway, so on the Rust side it’s set to an unreasonably
 /// large number.
 const MAX_CONTENT_LENGTH: u64 = 2 * 1024 * 1024 * 1024;
 
+/// The maximum size of a report to accept. Reports are short JSON
+/// documents, so anything larger is rejected outright.
+const MAX_REPORT_LENGTH: u64 = 16 * 1024;
+
+/// The maximum size of a name reservation to accept.
+const MAX_RESERVATION_LENGTH: u64 = 4 * 1024;
+
+/// The maximum size of the metadata starting a resumable upload.
+const MAX_METADATA_LENGTH: u64 = 64 * 1024;
+
 pub async fn format_rejection(
     logger: Arc<Logger>,
     rej: reject::Rejection,
 ) -> Result<WithStatus<Json>, reject::Rejection> {
     if let Some(r) = rej.find::<rejection::Rejection>() {
         let e = &r.error;
-        error!(logger, "Backend error"; "context" => ?r.context, "error" => ?r.error, "status" => %status_code_for(e), "message" => %r.error);
         let flattened = r.flatten();
+        error!(logger, "Backend error"; "context" => ?r.context, "error" => ?r.error, "status" => %status_code_for(e), "message" => %r.error, "request_id" => flattened.request_id.clone());
+        metrics::record_error(e, status_code_for(e));
 
         return Ok(with_status(json(&flattened), status_code_for(e)));
     }
@@ -35,68 +91,108 @@ pub async fn format_rejection(
     Err(rej)
 }
 
+/// Names a route after the full path of its handler, leaving, say,
+/// `upload` or `admin::hide`.
+pub(crate) fn route_name(handler: &'static str) -> &'static str {
+    handler
+        .trim_start_matches(ROUTE_PREFIX)
+        .trim_start_matches(PUBLIC_ROUTE_PREFIX)
+}
+
 fn status_code_for(e: &BackendError) -> StatusCode {
     use BackendError::*;
 
     match e {
-        BadRequest | TooManyStreams(..) => StatusCode::BAD_REQUEST,
-        BackendError::InvalidAudioFormat { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
-        InvalidId { .. }
+        BadRequest
         | PartsMissing
-        | MalformedUploadMetadata { .. }
-        | MalformedFormSubmission { .. } => StatusCode::BAD_REQUEST,
+        | MalformedUploadMetadata(..)
+        | TooManyStreams(..)
+        | MalformedFormSubmission
+        | InvalidId(..) => StatusCode::BAD_REQUEST,
+        NotAuthorized | InvalidToken { .. } => StatusCode::UNAUTHORIZED,
         NameAlreadyExists => StatusCode::FORBIDDEN,
-        InvalidToken { .. } => StatusCode::UNAUTHORIZED,
-        _ => StatusCode::INTERNAL_SERVER_ERROR,
+        NonExistentId(..)
+        | NonExistentToken(..)
+        | NonExistentKey(..)
+        | NonExistentLabel { .. }
+        | NonExistentTranslation { .. }
+        | NonExistentFormat(..)
+        | NonExistentBlockedTerm(..)
+        | NonExistentUpload(..) => StatusCode::NOT_FOUND,
+        RecordingHasChildren(..)
+        | LabelAlreadyExists
+        | FormatAlreadyExists
+        | BlockedTermAlreadyExists
+        | UploadOffsetMismatch { .. }
+        | UploadIncomplete(..) => StatusCode::CONFLICT,
+        RecordingDeleted(..) => StatusCode::GONE,
+        InvalidMetadata(..) => StatusCode::UNPROCESSABLE_ENTITY,
+        UploadTooLong { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        InvalidAudioFormat { .. } | UnrecognizedAudioFormat | UnsupportedContentType(..) => {
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        }
+        TooManyReports => StatusCode::TOO_MANY_REQUESTS,
+        DirectUploadsUnsupported => StatusCode::NOT_IMPLEMENTED,
+        StoreUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Sqlx { .. }
+        | StoreDeleteFailed { .. }
+        | StoreCheckFailed { .. }
+        | StoreCopyFailed { .. }
+        | StoreLoadFailed { .. }
+        | StoreLoadInterrupted(..)
+        | StoreListFailed { .. }
+        | UploadFailed { .. }
+        | LocalStoreFailed(..)
+        | FailedToGenerateUrl { .. }
+        | TemporaryFileError(..)
+        | FfprobeFailed(..)
+        | MalformedFfprobeOutput(..)
+        | MalformedResponse(..)
+        | IdAlreadyExists
+        | UnableToParseUrl { .. }
+        | TokenRollbackFailed { .. }
+        | RecordingDeleteFailed { .. }
+        | DeleteRollbackFailed { .. }
+        | SummarizedRecordingDeleteFailed { .. } => StatusCode::INTERNAL_SERVER_ERROR,
     }
 }
 
 mod internal {
     use uuid::Uuid;
     use warp::filters::multipart::form;
-    use warp::filters::BoxedFilter;
     use warp::Filter;
-    use warp::Reply;
-    use warp::{delete, get as g, path as p, path::end, post, query};
+    use warp::{body, header};
+    use warp::{delete, get as g, patch, path as p, path::end, post, query};
 
-    use super::{handlers, query as q, MAX_CONTENT_LENGTH};
+    use super::{
+        handlers, query as q, Route, MAX_CONTENT_LENGTH, MAX_METADATA_LENGTH, MAX_REPORT_LENGTH,
+        MAX_RESERVATION_LENGTH,
+    };
     use crate::environment::{Environment, SafeStore};
-
-    type Route = BoxedFilter<(Box<dyn Reply>,)>;
-
-    macro_rules! route_filter {
-    ($route_variable:ident; $first:expr) => (let $route_variable = $route_variable.and($first););
-    ($route_variable:ident; $first:expr, $($rest:expr),+) => (
-        let $route_variable = $route_variable.and($first);
-        route_filter!($route_variable; $($rest),+);
-    )
-}
-
-    macro_rules! route {
-    ($name:ident => $handler:ident, $route_variable:ident; $($filters:expr),+) => (
-        pub fn $name<O: SafeStore + 'static>(environment: Environment<O>) -> Route {
-            let $route_variable = warp::any()
-                .map(move || environment.clone());
-
-            route_filter!($route_variable; $($filters),+);
-
-            $route_variable.and_then(handlers::$handler)
-                .boxed()
-        }
-    );
-}
+    use crate::report::NewReport;
+    use crate::request_id;
+    use crate::reservation::NewReservation;
+    use crate::resumable::MAX_CHUNK_LENGTH;
 
     route!(make_formats_route => formats, rt; p!("formats"), g());
-    route!(make_ages_list_route => ages_list, rt; p!("ages"), g());
-    route!(make_categories_list_route => categories_list, rt; p!("categories"), g());
-    route!(make_genders_list_route => genders_list, rt; p!("genders"), g());
+    route!(make_ages_list_route => ages_list, rt; p!("ages"), g(), query::<q::LanguageQuery>(), header::optional::<String>("accept-language"));
+    route!(make_categories_list_route => categories_list, rt; p!("categories"), g(), query::<q::LanguageQuery>(), header::optional::<String>("accept-language"));
+    route!(make_genders_list_route => genders_list, rt; p!("genders"), g(), query::<q::LanguageQuery>(), header::optional::<String>("accept-language"));
     route!(make_count_route => count, rt; p!("count"), g());
     route!(make_upload_route => upload, rt; end(), post(), form().max_length(MAX_CONTENT_LENGTH));
     route!(make_children_route => children, rt; p!("id" / String / "children"), g());
     route!(make_delete_route => delete, rt; p!("id" / String), delete());
     route!(make_retrieve_route => retrieve, rt; p!("id" / String), g());
+    route!(make_audio_route => audio, rt; p!("id" / String / "audio"), g(), header::optional::<String>("range"), header::optional::<String>("if-none-match"));
     route!(make_random_route => random, rt; p!("random" / u8), g());
     route!(make_token_route => token, rt; p!("token" / Uuid), g());
     route!(make_lookup_route => lookup, rt; p!("lookup"/ String), g());
+    route!(make_report_route => report, rt; p!("id" / String / "report"), post(), request_id::remote(), header::optional::<String>("x-forwarded-for"), body::content_length_limit(MAX_REPORT_LENGTH), body::json::<NewReport>());
     route!(make_availability_route => availability, rt; p!("available" / ..), query::<q::AvailabilityQuery>(), end(), g());
+    route!(make_start_upload_route => start_upload, rt; p!("uploads"), post(), header::header::<u64>("upload-length"), body::content_length_limit(MAX_METADATA_LENGTH), body::bytes());
+    route!(make_upload_progress_route => upload_progress, rt; p!("uploads" / String), g());
+    route!(make_upload_chunk_route => upload_chunk, rt; p!("uploads" / String), patch(), header::header::<u64>("upload-offset"), body::content_length_limit(MAX_CHUNK_LENGTH), body::bytes());
+    route!(make_start_direct_upload_route => start_direct_upload, rt; p!("direct-uploads"), post(), header::header::<u64>("upload-length"), header::header::<String>("upload-content-type"), body::content_length_limit(MAX_METADATA_LENGTH), body::bytes());
+    route!(make_finalize_direct_upload_route => finalize_direct_upload, rt; p!("direct-uploads" / String / "finalize"), post());
+    route!(make_reserve_route => reserve, rt; p!("reserve"), post(), body::content_length_limit(MAX_RESERVATION_LENGTH), body::json::<NewReservation>());
 }
@@ -1,13 +1,31 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use futures::future::{BoxFuture, FutureExt};
+use log::{info, Logger};
+use uuid::Uuid;
 use warp::http::StatusCode;
 use warp::reject;
 use warp::reply::{json, Reply};
 use warp::Filter;
+use warp::{body, delete, get as g, path as p, post, put};
 
 use super::response::SuccessResponse;
-use crate::environment::Environment;
+use super::Route;
+use crate::blocklist::{self, NewBlockedTerm};
+use crate::environment::{Environment, SafeStore};
+use crate::label::{Id, Kind, LabelContents, Translation};
+use crate::metrics;
+use crate::mime_type::FormatContents;
+use crate::request_id::{self, REQUEST_ID_HEADER};
+
+mod auth;
+mod handlers;
+
+pub use auth::authenticate;
+
+/// The maximum size of a request body to accept on the admin server.
+const MAX_BODY_LENGTH: u64 = 64 * 1024;
 
 pub fn make_healthz_route<'a, O: Clone + Send + Sync + 'a>(
     _environment: Environment<O>,
@@ -21,6 +39,53 @@ pub fn make_healthz_route<'a, O: Clone + Send + Sync + 'a>(
     })
 }
 
+/// Exposes the server’s metrics in the Prometheus text format.
+pub fn make_metrics_route<'a, O: Clone + Send + Sync + 'a>(
+    _environment: Environment<O>,
+) -> impl warp::Filter<Extract = (impl Reply,), Error = reject::Rejection> + Clone + 'a {
+    warp::path("metrics").and(warp::get()).map(move || {
+        warp::reply::with_header(metrics::render(), "content-type", metrics::content_type())
+    })
+}
+
+/// Logs an audit line for every request to the admin server other
+/// than health checks and metrics scrapes, including those rejected
+/// for lack of credentials.
+pub fn audit(logger: Arc<Logger>) -> warp::log::Log<impl Fn(warp::log::Info) + Clone + Send> {
+    warp::log::custom(move |request: warp::log::Info| {
+        if matches!(request.path(), "/healthz" | "/readyz" | "/metrics") {
+            return;
+        }
+
+        info!(logger, "Admin request";
+            "audit" => true,
+            "method" => %request.method(),
+            "path" => request.path(),
+            "status" => request.status().as_u16(),
+            "remote" => ?request_id::remote_addr(),
+            "elapsed" => ?request.elapsed(),
+            "request_id" => request
+                .request_headers()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|id| id.to_str().ok()),
+        );
+    })
+}
+
+/// Reports whether the server’s dependencies are usable. `ffprobe` is
+/// only checked if a path to it is given.
+pub fn make_readyz_route<O: SafeStore + 'static>(
+    environment: Environment<O>,
+    ffprobe: Option<PathBuf>,
+) -> Route {
+    p!("readyz")
+        .and(g())
+        .map(move || (environment.clone(), ffprobe.clone()))
+        .untuple_one()
+        .and_then(handlers::readyz)
+        .boxed()
+}
+
 type TerminationFuture<'a> = BoxFuture<'a, ()>;
 
 type TerminationFunctionWrapper<'a> = Arc<dyn Fn() -> TerminationFuture<'a> + Send + Sync + 'a>;
@@ -44,3 +109,24 @@ pub fn make_termination_route<'a, O: Clone + Send + Sync + 'a>(
 
     warp::path("terminate").and(warp::post()).and_then(handler)
 }
+
+route!(make_recent_route => recent, rt; p!("recent" / u8), g());
+route!(make_hide_route => hide, rt; p!("id" / String / "hide"), post());
+route!(make_restore_route => restore, rt; p!("id" / String / "restore"), post());
+route!(make_purge_route => purge, rt; p!("id" / String), delete());
+route!(make_reports_route => reports, rt; p!("reports"), g());
+route!(make_resolve_report_route => resolve_report, rt; p!("reports" / Uuid / "resolve"), post());
+route!(make_dismiss_report_route => dismiss_report, rt; p!("reports" / Uuid / "dismiss"), post());
+route!(make_labels_route => labels, rt; p!("labels" / Kind), g());
+route!(make_create_label_route => create_label, rt; p!("labels" / Kind), post(), body::content_length_limit(MAX_BODY_LENGTH), body::json::<LabelContents>());
+route!(make_update_label_route => update_label, rt; p!("labels" / Kind / Id), put(), body::content_length_limit(MAX_BODY_LENGTH), body::json::<LabelContents>());
+route!(make_reorder_labels_route => reorder_labels, rt; p!("labels" / Kind / "order"), put(), body::content_length_limit(MAX_BODY_LENGTH), body::json::<Vec<Id>>());
+route!(make_save_translation_route => save_translation, rt; p!("labels" / Kind / Id / "translations" / String), put(), body::content_length_limit(MAX_BODY_LENGTH), body::json::<Translation>());
+route!(make_delete_translation_route => delete_translation, rt; p!("labels" / Kind / Id / "translations" / String), delete());
+route!(make_formats_route => formats, rt; p!("formats"), g());
+route!(make_create_format_route => create_format, rt; p!("formats"), post(), body::content_length_limit(MAX_BODY_LENGTH), body::json::<FormatContents>());
+route!(make_update_format_route => update_format, rt; p!("formats" / Id), put(), body::content_length_limit(MAX_BODY_LENGTH), body::json::<FormatContents>());
+route!(make_delete_format_route => delete_format, rt; p!("formats" / Id), delete());
+route!(make_blocklist_route => blocklist, rt; p!("blocklist"), g());
+route!(make_block_term_route => block_term, rt; p!("blocklist"), post(), body::content_length_limit(MAX_BODY_LENGTH), body::json::<NewBlockedTerm>());
+route!(make_unblock_term_route => unblock_term, rt; p!("blocklist" / blocklist::Id), delete());
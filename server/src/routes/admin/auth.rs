@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use warp::{header, reject, Filter};
+
+use crate::errors::BackendError;
+use crate::routes::rejection::{Context, Rejection};
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// Requires requests to carry `Authorization: Bearer <token>` with
+/// the given token.
+///
+/// Both tokens are trimmed, since a token read from a file easily
+/// picks up a newline, and hashed before being compared in constant
+/// time, so neither the contents nor the length of the expected token
+/// leak through timing.
+pub fn authenticate(
+    token: &str,
+) -> impl Filter<Extract = (), Error = reject::Rejection> + Clone + Send + Sync + 'static {
+    let expected = Arc::new(Sha256::digest(token.trim().as_bytes()).to_vec());
+
+    header::optional::<String>("authorization")
+        .and_then(move |authorization: Option<String>| {
+            let expected = expected.clone();
+
+            async move {
+                if is_authorized(&expected, authorization.as_deref()) {
+                    Ok(())
+                } else {
+                    Err(reject::custom(Rejection::new(
+                        Context::authentication(),
+                        BackendError::NotAuthorized,
+                    )))
+                }
+            }
+        })
+        .untuple_one()
+}
+
+fn is_authorized(expected: &[u8], authorization: Option<&str>) -> bool {
+    authorization
+        .and_then(|a| a.strip_prefix(BEARER_PREFIX))
+        .map_or(false, |presented| {
+            let presented = Sha256::digest(presented.trim().as_bytes());
+
+            presented.as_slice().ct_eq(expected).into()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+
+    use super::is_authorized;
+
+    #[test]
+    fn only_accepts_the_right_bearer_token() {
+        let expected = Sha256::digest(b"secret").to_vec();
+
+        assert!(is_authorized(&expected, Some("Bearer secret")));
+        assert!(is_authorized(&expected, Some("Bearer secret\n")));
+        assert!(!is_authorized(&expected, Some("Bearer secret2")));
+        assert!(!is_authorized(&expected, Some("Bearer ")));
+        assert!(!is_authorized(&expected, Some("Basic secret")));
+        assert!(!is_authorized(&expected, Some("secret")));
+        assert!(!is_authorized(&expected, None));
+    }
+}
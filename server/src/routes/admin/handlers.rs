@@ -0,0 +1,422 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use log::{info, warn, Logger};
+use uuid::Uuid;
+use warp::http::StatusCode;
+use warp::reject;
+use warp::reply::{json, with_status, Reply};
+
+use crate::audio::check_ffprobe;
+use crate::blocklist::{self, NewBlockedTerm};
+use crate::environment::{Environment, SafeStore};
+use crate::errors::BackendError;
+use crate::label::{Id, Kind, LabelContents, Translation};
+use crate::locale::normalize_tag;
+use crate::mime_type::FormatContents;
+use crate::recording::Recording;
+use crate::report::Resolution;
+use crate::routes::{
+    rejection::{Context, Rejection},
+    response::{Check, SuccessResponse},
+};
+
+type RouteResult = Result<Box<dyn Reply>, reject::Rejection>;
+
+/// How long to wait for each dependency when checking readiness.
+const READINESS_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub async fn readyz<O: SafeStore>(
+    environment: Environment<O>,
+    ffprobe: Option<PathBuf>,
+) -> RouteResult {
+    let logger = &environment.logger;
+
+    let ffprobe = async move {
+        match ffprobe {
+            Some(path) => Some(check(logger, "ffprobe", check_ffprobe(path)).await),
+            None => None,
+        }
+    };
+
+    let (database, store, ffprobe) = tokio::join!(
+        check(logger, "database", environment.db.check()),
+        check(logger, "store", environment.store.check()),
+        ffprobe,
+    );
+
+    let mut checks = BTreeMap::new();
+    checks.insert("database", database);
+    checks.insert("store", store);
+
+    if let Some(ffprobe) = ffprobe {
+        checks.insert("ffprobe", ffprobe);
+    }
+
+    let ready = checks.values().all(|c| c.ok);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    Ok(Box::new(with_status(
+        json(&SuccessResponse::Readyz { ready, checks }),
+        status,
+    )))
+}
+
+// the report is served without credentials, so it only gets the terse
+// top-level message, and the causes are logged instead
+async fn check(
+    logger: &Logger,
+    dependency: &'static str,
+    future: impl Future<Output = Result<(), BackendError>>,
+) -> Check {
+    match tokio::time::timeout(READINESS_TIMEOUT, future).await {
+        Ok(Ok(())) => Check::passed(),
+        Ok(Err(e)) => {
+            warn!(logger, "Readiness check failed"; "dependency" => dependency, "error" => describe(&e));
+            Check::failed(e.to_string())
+        }
+        Err(_) => {
+            let error = format!("timed out after {:?}", READINESS_TIMEOUT);
+            warn!(logger, "Readiness check failed"; "dependency" => dependency, "error" => &error);
+            Check::failed(error)
+        }
+    }
+}
+
+// the top-level messages are deliberately terse, so the causes are
+// spelled out to make failures diagnosable from the logs
+fn describe(error: &BackendError) -> String {
+    let mut description = error.to_string();
+    let mut source = error.source();
+
+    while let Some(e) = source {
+        description.push_str(": ");
+        description.push_str(&e.to_string());
+        source = e.source();
+    }
+
+    description
+}
+
+pub async fn recent<O: SafeStore>(environment: Environment<O>, count: u8) -> RouteResult {
+    let count = count as i16;
+
+    let recordings = environment
+        .db
+        .retrieve_recent(count)
+        .await
+        .map_err(|e| Rejection::new(Context::recent(count), e))?;
+
+    Ok(Box::new(json(&SuccessResponse::Recent { recordings })))
+}
+
+pub async fn hide<O: SafeStore>(environment: Environment<O>, id: String) -> RouteResult {
+    let error_handler = |e: BackendError| Rejection::new(Context::hide(id.clone()), e);
+
+    let id = parse_id(&id).map_err(error_handler)?;
+    info!(environment.logger, "Hiding recording..."; "id" => format!("{}", &id));
+
+    environment.db.hide(&id).await.map_err(error_handler)?;
+
+    Ok(Box::new(StatusCode::NO_CONTENT))
+}
+
+pub async fn restore<O: SafeStore>(environment: Environment<O>, id: String) -> RouteResult {
+    let error_handler = |e: BackendError| Rejection::new(Context::restore(id.clone()), e);
+
+    let id = parse_id(&id).map_err(error_handler)?;
+    info!(environment.logger, "Restoring recording..."; "id" => format!("{}", &id));
+
+    environment.db.restore(&id).await.map_err(error_handler)?;
+
+    Ok(Box::new(StatusCode::NO_CONTENT))
+}
+
+pub async fn purge<O: SafeStore>(environment: Environment<O>, id: String) -> RouteResult {
+    let error_handler = |e: BackendError| Rejection::new(Context::purge(id.clone()), e);
+
+    let id = parse_id(&id).map_err(error_handler)?;
+    info!(environment.logger, "Purging recording..."; "id" => format!("{}", &id));
+
+    // deleted recordings have already had their audio removed
+    let key = match environment.db.retrieve(&id).await.map_err(error_handler)? {
+        Some(Recording::Active(recording)) => recording.object_key().map(str::to_owned),
+        _ => None,
+    };
+
+    // the row goes first so that a failure can’t leave a recording
+    // pointing at audio that no longer exists; an orphaned object in
+    // the store is the lesser evil
+    environment.db.purge(&id).await.map_err(error_handler)?;
+
+    if let Some(key) = key {
+        environment
+            .store
+            .delete(&key)
+            .await
+            .map_err(error_handler)?;
+    }
+
+    Ok(Box::new(StatusCode::NO_CONTENT))
+}
+
+pub async fn reports<O: SafeStore>(environment: Environment<O>) -> RouteResult {
+    let reports = environment
+        .db
+        .retrieve_open_reports()
+        .await
+        .map_err(|e| Rejection::new(Context::reports(), e))?;
+
+    Ok(Box::new(json(&SuccessResponse::Reports { reports })))
+}
+
+pub async fn resolve_report<O: SafeStore>(environment: Environment<O>, id: Uuid) -> RouteResult {
+    close_report(environment, id, Resolution::Resolved).await
+}
+
+pub async fn dismiss_report<O: SafeStore>(environment: Environment<O>, id: Uuid) -> RouteResult {
+    close_report(environment, id, Resolution::Dismissed).await
+}
+
+async fn close_report<O: SafeStore>(
+    environment: Environment<O>,
+    id: Uuid,
+    resolution: Resolution,
+) -> RouteResult {
+    info!(environment.logger, "Closing report..."; "id" => format!("{}", &id), "resolution" => resolution.as_str());
+
+    environment
+        .db
+        .resolve_report(&id, resolution)
+        .await
+        .map_err(|e| Rejection::new(Context::resolve_report(id.to_string()), e))?;
+
+    Ok(Box::new(StatusCode::NO_CONTENT))
+}
+
+pub async fn labels<O: SafeStore>(environment: Environment<O>, kind: Kind) -> RouteResult {
+    let labels = environment
+        .db
+        .retrieve_labels(kind)
+        .await
+        .map_err(|e| Rejection::new(Context::labels(kind.to_string()), e))?;
+
+    Ok(Box::new(json(&labels)))
+}
+
+pub async fn create_label<O: SafeStore>(
+    environment: Environment<O>,
+    kind: Kind,
+    contents: LabelContents,
+) -> RouteResult {
+    info!(environment.logger, "Creating label..."; "kind" => %kind, "label" => &contents.label);
+
+    let id = environment
+        .db
+        .create_label(kind, contents)
+        .await
+        .map_err(|e| Rejection::new(Context::create_label(kind.to_string()), e))?;
+
+    Ok(Box::new(with_status(
+        json(&SuccessResponse::Label { id }),
+        StatusCode::CREATED,
+    )))
+}
+
+pub async fn update_label<O: SafeStore>(
+    environment: Environment<O>,
+    kind: Kind,
+    id: Id,
+    contents: LabelContents,
+) -> RouteResult {
+    info!(environment.logger, "Updating label..."; "kind" => %kind, "id" => id, "enabled" => contents.enabled);
+
+    environment
+        .db
+        .update_label(kind, id, contents)
+        .await
+        .map_err(|e| Rejection::new(Context::update_label(kind.to_string(), id), e))?;
+
+    Ok(Box::new(StatusCode::NO_CONTENT))
+}
+
+pub async fn reorder_labels<O: SafeStore>(
+    environment: Environment<O>,
+    kind: Kind,
+    ids: Vec<Id>,
+) -> RouteResult {
+    info!(environment.logger, "Reordering labels..."; "kind" => %kind, "ids" => ?ids);
+
+    environment
+        .db
+        .reorder_labels(kind, ids)
+        .await
+        .map_err(|e| Rejection::new(Context::reorder_labels(kind.to_string()), e))?;
+
+    Ok(Box::new(StatusCode::NO_CONTENT))
+}
+
+pub async fn save_translation<O: SafeStore>(
+    environment: Environment<O>,
+    kind: Kind,
+    id: Id,
+    locale: String,
+    translation: Translation,
+) -> RouteResult {
+    let locale = normalize_tag(locale);
+    info!(environment.logger, "Saving translation..."; "kind" => %kind, "id" => id, "locale" => &locale);
+
+    environment
+        .db
+        .save_translation(kind, id, &locale, translation)
+        .await
+        .map_err(|e| {
+            Rejection::new(
+                Context::translation(kind.to_string(), id, locale.clone()),
+                e,
+            )
+        })?;
+
+    Ok(Box::new(StatusCode::NO_CONTENT))
+}
+
+pub async fn delete_translation<O: SafeStore>(
+    environment: Environment<O>,
+    kind: Kind,
+    id: Id,
+    locale: String,
+) -> RouteResult {
+    let locale = normalize_tag(locale);
+    info!(environment.logger, "Deleting translation..."; "kind" => %kind, "id" => id, "locale" => &locale);
+
+    environment
+        .db
+        .delete_translation(kind, id, &locale)
+        .await
+        .map_err(|e| {
+            Rejection::new(
+                Context::translation(kind.to_string(), id, locale.clone()),
+                e,
+            )
+        })?;
+
+    Ok(Box::new(StatusCode::NO_CONTENT))
+}
+
+pub async fn formats<O: SafeStore>(environment: Environment<O>) -> RouteResult {
+    let formats = environment
+        .db
+        .retrieve_formats()
+        .await
+        .map_err(|e| Rejection::new(Context::formats(), e))?;
+
+    Ok(Box::new(json(&formats)))
+}
+
+pub async fn create_format<O: SafeStore>(
+    environment: Environment<O>,
+    contents: FormatContents,
+) -> RouteResult {
+    info!(environment.logger, "Creating audio format..."; "container" => &contents.container, "codec" => &contents.codec, "essence" => &contents.essence);
+
+    let context = Context::create_format(contents.container.clone(), contents.codec.clone());
+
+    let id = environment
+        .db
+        .create_format(contents)
+        .await
+        .map_err(|e| Rejection::new(context, e))?;
+
+    Ok(Box::new(with_status(
+        json(&SuccessResponse::Format { id }),
+        StatusCode::CREATED,
+    )))
+}
+
+pub async fn update_format<O: SafeStore>(
+    environment: Environment<O>,
+    id: Id,
+    contents: FormatContents,
+) -> RouteResult {
+    info!(environment.logger, "Updating audio format..."; "id" => id, "enabled" => contents.enabled);
+
+    environment
+        .db
+        .update_format(id, contents)
+        .await
+        .map_err(|e| Rejection::new(Context::format(id), e))?;
+
+    Ok(Box::new(StatusCode::NO_CONTENT))
+}
+
+pub async fn delete_format<O: SafeStore>(environment: Environment<O>, id: Id) -> RouteResult {
+    info!(environment.logger, "Deleting audio format..."; "id" => id);
+
+    environment
+        .db
+        .delete_format(id)
+        .await
+        .map_err(|e| Rejection::new(Context::format(id), e))?;
+
+    Ok(Box::new(StatusCode::NO_CONTENT))
+}
+
+pub async fn blocklist<O: SafeStore>(environment: Environment<O>) -> RouteResult {
+    let terms = environment
+        .db
+        .retrieve_blocked_terms()
+        .await
+        .map_err(|e| Rejection::new(Context::blocklist(), e))?;
+
+    Ok(Box::new(json(&terms)))
+}
+
+pub async fn block_term<O: SafeStore>(
+    environment: Environment<O>,
+    term: NewBlockedTerm,
+) -> RouteResult {
+    info!(environment.logger, "Blocking term..."; "term" => &term.term, "whole_word" => term.whole_word);
+
+    let context = Context::block_term(term.term.clone());
+
+    if term.term.is_empty() {
+        return Err(Rejection::new(context, BackendError::BadRequest).into());
+    }
+
+    let id = environment
+        .db
+        .create_blocked_term(term)
+        .await
+        .map_err(|e| Rejection::new(context, e))?;
+
+    Ok(Box::new(with_status(
+        json(&SuccessResponse::BlockedTerm { id }),
+        StatusCode::CREATED,
+    )))
+}
+
+pub async fn unblock_term<O: SafeStore>(
+    environment: Environment<O>,
+    id: blocklist::Id,
+) -> RouteResult {
+    info!(environment.logger, "Unblocking term..."; "id" => id);
+
+    environment
+        .db
+        .delete_blocked_term(id)
+        .await
+        .map_err(|e| Rejection::new(Context::blocked_term(id), e))?;
+
+    Ok(Box::new(StatusCode::NO_CONTENT))
+}
+
+fn parse_id(id: &str) -> Result<Uuid, BackendError> {
+    Uuid::parse_str(id).map_err(|_| BackendError::InvalidId(id.to_owned()))
+}
@@ -1,7 +1,10 @@
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use log::{debug, error, trace, Logger};
+use bytes::Bytes;
+use log::{debug, error, info, trace, Logger};
+use time::OffsetDateTime;
 use url::Url;
 use uuid::Uuid;
 use warp::{
@@ -11,23 +14,40 @@ use warp::{
     reply::{json, with_header, with_status, Reply},
 };
 
+use crate::blocklist;
+use crate::direct::{staging_key, DirectUploadTarget};
 use crate::environment::{Environment, SafeStore};
 use crate::errors::{summarize_delete_errors, BackendError};
-use crate::io::parse_upload;
-use crate::recording::UploadMetadata;
+use crate::io::{parse_upload, part_as_vec};
+use crate::locale::preferred_locales;
+use crate::metrics;
+use crate::recording::{ActiveRecording, UploadMetadata};
+use crate::report::NewReport;
+use crate::reservation::NewReservation;
+use crate::resumable::{UploadProgress, UploadSession};
 use crate::routes::{
-    query::AvailabilityQuery,
+    query::{AvailabilityQuery, LanguageQuery},
     rejection::{Context, Rejection},
     response::SuccessResponse,
+    MAX_CONTENT_LENGTH,
 };
+use crate::suggestions;
+use crate::telemetry::in_span;
+use crate::validation::{self, MetadataLimits};
 use crate::{audio::format::AudioFormat, db::Db, environment, mime_type::MimeType};
 
 const SERVER_TIMING_HEADER: &str = "server-timing";
+const UPLOAD_OFFSET_HEADER: &str = "upload-offset";
+const VARY_HEADER: &str = "vary";
+const ACCEPT_LANGUAGE_HEADER: &str = "accept-language";
 type RouteResult = Result<Box<dyn Reply>, reject::Rejection>;
 
 macro_rules! timed {
-    ($($expression:stmt);+) => {
+    ($environment:ident; $($expression:stmt);+) => {
         let start = Instant::now();
+        let trace =
+            crate::telemetry::start_handler(&crate::request_id::trace(), $environment.route);
+        let $environment = $environment.traced(trace);
 
         // TODO when `try` blocks are stabilized, we can wrap the body
         // and return the headers even on errors
@@ -42,7 +62,7 @@ macro_rules! timed {
 }
 
 pub async fn formats<O: SafeStore>(environment: Environment<O>) -> RouteResult {
-    timed! {
+    timed! { environment;
         let formats = environment
             .db
             .retrieve_format_essences()
@@ -54,47 +74,65 @@ pub async fn formats<O: SafeStore>(environment: Environment<O>) -> RouteResult {
     }
 }
 
-pub async fn ages_list<O: SafeStore>(environment: Environment<O>) -> RouteResult {
-    timed! {
+pub async fn ages_list<O: SafeStore>(
+    environment: Environment<O>,
+    query: LanguageQuery,
+    accept_language: Option<String>,
+) -> RouteResult {
+    timed! { environment;
+        let locales = preferred_locales(query.lang.as_deref(), accept_language.as_deref());
+
         let ages = environment
             .db
-            .retrieve_ages()
+            .retrieve_ages(&locales)
             .await
             .map_err(|e: BackendError| Rejection::new(Context::ages(), e))?;
 
         // TODO make this cacheable
-        json(&ages)
+        with_header(json(&ages), VARY_HEADER, ACCEPT_LANGUAGE_HEADER)
     }
 }
 
-pub async fn categories_list<O: SafeStore>(environment: Environment<O>) -> RouteResult {
-    timed! {
-    let categories = environment
-        .db
-        .retrieve_categories()
-        .await
-        .map_err(|e: BackendError| Rejection::new(Context::categories(), e))?;
+pub async fn categories_list<O: SafeStore>(
+    environment: Environment<O>,
+    query: LanguageQuery,
+    accept_language: Option<String>,
+) -> RouteResult {
+    timed! { environment;
+        let locales = preferred_locales(query.lang.as_deref(), accept_language.as_deref());
+
+        let categories = environment
+            .db
+            .retrieve_categories(&locales)
+            .await
+            .map_err(|e: BackendError| Rejection::new(Context::categories(), e))?;
 
         // TODO make this cacheable
-        json(&categories)
+        with_header(json(&categories), VARY_HEADER, ACCEPT_LANGUAGE_HEADER)
     }
 }
 
-pub async fn genders_list<O: SafeStore>(environment: Environment<O>) -> RouteResult {
-    timed! {
+pub async fn genders_list<O: SafeStore>(
+    environment: Environment<O>,
+    query: LanguageQuery,
+    accept_language: Option<String>,
+) -> RouteResult {
+    timed! { environment;
+        let locales = preferred_locales(query.lang.as_deref(), accept_language.as_deref());
+
         let genders = environment
             .db
-            .retrieve_genders()
+            .retrieve_genders(&locales)
             .await
             .map_err(|e: BackendError| Rejection::new(Context::genders(), e))?;
 
         // TODO make this cacheable
-        json(&genders)
+        with_header(json(&genders), VARY_HEADER, ACCEPT_LANGUAGE_HEADER)
     }
 }
 
 pub async fn count<O: SafeStore>(environment: Environment<O>) -> RouteResult {
-    timed! {
+    timed! { environment;
         let count = environment
             .db
             .count_all()
@@ -111,98 +149,414 @@ pub async fn upload<O: SafeStore + 'static>(
 ) -> RouteResult {
     use log::o;
 
-    timed! {
+    timed! { environment;
         let Environment {
             logger,
             db,
-            checker,
+            trace,
             ..
         } = environment.clone();
 
         let error_handler = |e: BackendError| Rejection::new(Context::upload(None), e);
 
-        debug!(logger, "Parsing submission...");
-        let upload = parse_upload(content).await.map_err(error_handler)?;
+        let (audio, metadata) = in_span(&trace, "parse", async {
+            debug!(logger, "Parsing submission...");
+            let upload = parse_upload(content).await?;
+
+            debug!(logger, "Parsing recording metadata...");
+            let metadata = parse_recording_metadata(logger.clone(), upload.metadata).await?;
+
+            let audio = part_as_vec(upload.audio)
+                .await
+                .map_err(|_| BackendError::MalformedFormSubmission)?;
+
+            Ok::<_, BackendError>((audio, metadata))
+        })
+        .await
+        .map_err(error_handler)?;
+
+        debug!(logger, "Validating recording metadata...");
+        in_span(
+            &trace,
+            "validate",
+            validate_metadata(db.clone(), &environment.config.metadata_limits, &metadata),
+        )
+        .await
+        .map_err(error_handler)?;
+
+        let token = metadata.token;
+
+        let logger = Arc::new(logger.new(o!("token" => format!("{}", token.clone()))));
+
+        debug!(logger, "Locking token...");
+        let parent_id = in_span(&trace, "lock_token", lock_token(logger.clone(), db.clone(), token))
+            .await
+            .map_err(error_handler)?;
+
+        let logger = Arc::new(logger.new(o!("parent_id" => format!("{}", parent_id.clone()))));
+
+        process_upload(environment.clone(), logger, parent_id, metadata, audio).await?
+    }
+}
+
+pub async fn start_upload<O: SafeStore>(
+    environment: Environment<O>,
+    length: u64,
+    raw_metadata: Bytes,
+) -> RouteResult {
+    use log::o;
+
+    timed! { environment;
+        let Environment { logger, db, config, urls, .. } = environment;
+
+        let error_handler = |e: BackendError| Rejection::new(Context::resumable_upload(None), e);
+
+        if length == 0 {
+            return Err(error_handler(BackendError::BadRequest).into());
+        };
+
+        if length > MAX_CONTENT_LENGTH {
+            return Err(error_handler(BackendError::UploadTooLong {
+                length: MAX_CONTENT_LENGTH,
+            })
+            .into());
+        };
 
         debug!(logger, "Parsing recording metadata...");
-        let metadata = parse_recording_metadata(logger.clone(), upload.metadata)
+        let metadata: UploadMetadata = serde_json::from_slice(&raw_metadata)
+            .map_err(BackendError::MalformedUploadMetadata)
+            .map_err(error_handler)?;
+
+        debug!(logger, "Validating recording metadata...");
+        validate_metadata(db.clone(), &config.metadata_limits, &metadata)
             .await
             .map_err(error_handler)?;
 
         let token = metadata.token;
 
-        let logger = Arc::new(logger.new(o!("token" => format!("{}", token.clone()))));
+        let logger = Arc::new(logger.new(o!("token" => format!("{}", token))));
 
         debug!(logger, "Locking token...");
         let parent_id = lock_token(logger.clone(), db.clone(), token)
             .await
             .map_err(error_handler)?;
 
-        let logger = Arc::new(logger.new(o!("parent_id" => format!("{}", parent_id.clone()))));
+        debug!(logger, "Starting resumable upload..."; "length" => length);
+        let id = db
+            .create_upload_session(&parent_id, &metadata, length)
+            .await
+            .map_err(|e| {
+                release_token_in_background(logger.clone(), db.clone(), token);
+                error_handler(e)
+            })?;
+
+        let progress = UploadProgress {
+            id,
+            offset: 0,
+            length,
+        };
+
+        with_header(
+            with_header(
+                with_status(json(&progress), StatusCode::CREATED),
+                "location",
+                urls.upload(&id).as_str(),
+            ),
+            UPLOAD_OFFSET_HEADER,
+            "0",
+        )
+    }
+}
+
+pub async fn upload_progress<O: SafeStore>(environment: Environment<O>, id: String) -> RouteResult {
+    timed! { environment;
+        let error_handler =
+            |e: BackendError| Rejection::new(Context::resumable_upload(Some(id.clone())), e);
+
+        let id = Uuid::parse_str(&id)
+            .map_err(|_| BackendError::InvalidId(id.clone()))
+            .map_err(error_handler)?;
+
+        let session = retrieve_upload_session(environment.db.clone(), &id)
+            .await
+            .map_err(error_handler)?;
+
+        with_header(
+            json(&UploadProgress::from(&session)),
+            UPLOAD_OFFSET_HEADER,
+            session.received.to_string(),
+        )
+    }
+}
+
+pub async fn upload_chunk<O: SafeStore + 'static>(
+    environment: Environment<O>,
+    id: String,
+    offset: u64,
+    chunk: Bytes,
+) -> RouteResult {
+    use log::o;
+
+    timed! { environment;
+        let error_handler =
+            |e: BackendError| Rejection::new(Context::resumable_upload(Some(id.clone())), e);
+
+        let id = Uuid::parse_str(&id)
+            .map_err(|_| BackendError::InvalidId(id.clone()))
+            .map_err(error_handler)?;
+
+        let Environment { logger, db, spool, .. } = environment.clone();
+        let logger = Arc::new(logger.new(o!("upload" => format!("{}", id), "offset" => offset)));
+
+        let session = retrieve_upload_session(db.clone(), &id)
+            .await
+            .map_err(error_handler)?;
+
+        if offset != session.received {
+            return Err(error_handler(BackendError::UploadOffsetMismatch {
+                expected: session.received,
+                actual: offset,
+            })
+            .into());
+        };
+
+        let chunk_length = chunk.len() as u64;
+
+        if chunk_length == 0 {
+            return Err(error_handler(BackendError::BadRequest).into());
+        };
+
+        if offset + chunk_length > session.length {
+            return Err(error_handler(BackendError::UploadTooLong {
+                length: session.length,
+            })
+            .into());
+        };
 
-        let error_handler = |e: BackendError| {
-            // first spawn a task to release the token, logging any
-            // errors, then go back to normal error handling
-            let logger = logger.clone();
-            let db = db.clone();
-            let token = token;
-
-            tokio::spawn(async move {
-                release_token(logger.clone(), db.clone(), token)
-                    .await
-                    .map_err(|e| {
-                        error!(logger, "Failed to release token: {}", e);
-                    })
-            });
-
-            error_handler(e)
+        debug!(logger, "Spooling chunk..."; "length" => chunk_length);
+        let path = spool
+            .write_chunk(&id, offset, chunk)
+            .await
+            .map_err(BackendError::TemporaryFileError)
+            .map_err(error_handler)?;
+
+        let received = match db.advance_upload_session(&id, offset, chunk_length).await {
+            Ok(Some(received)) => received,
+            other => {
+                if let Err(e) = spool.discard_chunk(path).await {
+                    error!(logger, "Failed to discard chunk: {}", e);
+                }
+
+                let error = match other {
+                    Err(e) => e,
+                    // another request sent a chunk at this offset first
+                    _ => match retrieve_upload_session(db.clone(), &id).await {
+                        Ok(session) => BackendError::UploadOffsetMismatch {
+                            expected: session.received,
+                            actual: offset,
+                        },
+                        Err(e) => e,
+                    },
+                };
+
+                return Err(error_handler(error).into());
+            }
         };
 
-        debug!(logger, "Verifying audio contents...");
-        let (verified_audio, audio_format) = verify_audio(logger.clone(), checker, upload.audio)
+        if let Err(e) = spool.accept_chunk(&id, offset, path.clone()).await {
+            // otherwise the upload would wait forever for a chunk it
+            // thinks it already has
+            if let Err(e) = db.rewind_upload_session(&id, offset, chunk_length).await {
+                error!(logger, "Failed to rewind upload: {}", e);
+            }
+
+            if let Err(e) = spool.discard_chunk(path).await {
+                error!(logger, "Failed to discard chunk: {}", e);
+            }
+
+            return Err(error_handler(BackendError::TemporaryFileError(e)).into());
+        }
+
+        if received < session.length {
+            Box::new(with_header(
+                StatusCode::NO_CONTENT,
+                UPLOAD_OFFSET_HEADER,
+                received.to_string(),
+            )) as Box<dyn Reply>
+        } else {
+            // the upload is over either way, so that no more chunks
+            // are accepted while it's processed
+            debug!(logger, "Assembling upload...");
+            db.delete_upload_session(&id).await.map_err(error_handler)?;
+
+            let audio = spool.assemble(&id, session.length).await;
+
+            if let Err(e) = spool.remove(&id).await {
+                error!(logger, "Failed to remove spooled chunks: {}", e);
+            }
+
+            let token = session.token;
+            let parent_id = session.parent_id;
+            let logger = Arc::new(logger.new(o!(
+                "token" => format!("{}", token),
+                "parent_id" => format!("{}", parent_id),
+            )));
+
+            let audio = audio.map_err(|e| {
+                release_token_in_background(logger.clone(), db.clone(), token);
+                error_handler(BackendError::TemporaryFileError(e))
+            })?;
+
+            process_upload(environment.clone(), logger, parent_id, session.metadata, audio).await?
+        }
+    }
+}
+
+pub async fn start_direct_upload<O: SafeStore>(
+    environment: Environment<O>,
+    length: u64,
+    content_type: String,
+    raw_metadata: Bytes,
+) -> RouteResult {
+    use log::o;
+
+    timed! { environment;
+        let Environment { logger, db, store, config, urls, .. } = environment;
+
+        let error_handler = |e: BackendError| Rejection::new(Context::direct_upload(None), e);
+
+        if length == 0 {
+            return Err(error_handler(BackendError::BadRequest).into());
+        };
+
+        if length > MAX_CONTENT_LENGTH {
+            return Err(error_handler(BackendError::UploadTooLong {
+                length: MAX_CONTENT_LENGTH,
+            })
+            .into());
+        };
+
+        // the content type is part of the signed request, so it has to
+        // be one the audio can turn out to have
+        let essences = db.retrieve_format_essences().await.map_err(error_handler)?;
+
+        if !essences.contains(&content_type) {
+            return Err(error_handler(BackendError::UnsupportedContentType(content_type)).into());
+        };
+
+        debug!(logger, "Parsing recording metadata...");
+        let metadata: UploadMetadata = serde_json::from_slice(&raw_metadata)
+            .map_err(BackendError::MalformedUploadMetadata)
+            .map_err(error_handler)?;
+
+        debug!(logger, "Validating recording metadata...");
+        validate_metadata(db.clone(), &config.metadata_limits, &metadata)
             .await
-            .map_err(&error_handler)?;
+            .map_err(error_handler)?;
+
+        let id = Uuid::new_v4();
+        let expires_at = OffsetDateTime::now_utc() + config.upload_url_duration;
 
-        // TODO retry in case ID already exists
-        debug!(logger, "Writing metadata to database...");
-        let email = metadata.email.clone(); // save for later
-        let id = save_recording_metadata(logger.clone(), db.clone(), &parent_id, metadata)
+        debug!(logger, "Signing upload request..."; "upload" => %id);
+        let request = store
+            .presign_save(&staging_key(&id), &content_type, length, config.upload_url_duration)
+            .map_err(|source| BackendError::FailedToGenerateUrl { source })
+            .and_then(|request| request.ok_or(BackendError::DirectUploadsUnsupported))
+            .map_err(error_handler)?;
+
+        let token = metadata.token;
+
+        let logger = Arc::new(logger.new(o!("token" => format!("{}", token))));
+
+        debug!(logger, "Locking token...");
+        let parent_id = lock_token(logger.clone(), db.clone(), token)
             .await
-            .map_err(&error_handler)?;
-        let id_as_str = format!("{}", id);
-        let logger = Arc::new(logger.new(o!("id" => id_as_str.clone())));
+            .map_err(error_handler)?;
 
-        let error_handler = |e: BackendError| {
-            // TODO delete row from DB
-            Rejection::new(Context::upload(Some(id_as_str.clone())), e)
+        debug!(logger, "Starting direct upload..."; "upload" => %id, "length" => length);
+        db.create_direct_upload(&id, &parent_id, &metadata, length)
+            .await
+            .map_err(|e| {
+                release_token_in_background(logger.clone(), db.clone(), token);
+                error_handler(e)
+            })?;
+
+        let target = DirectUploadTarget {
+            id,
+            method: "PUT",
+            url: request.url,
+            headers: request.headers,
+            expires_at,
         };
 
-        // should this punt to a queue? is that necessary?
-        debug!(logger, "Saving recording to store...");
+        with_header(
+            with_status(json(&target), StatusCode::CREATED),
+            "location",
+            urls.direct_upload(&id).as_str(),
+        )
+    }
+}
+
+pub async fn finalize_direct_upload<O: SafeStore + 'static>(
+    environment: Environment<O>,
+    id: String,
+) -> RouteResult {
+    use log::o;
+
+    timed! { environment;
+        let error_handler =
+            |e: BackendError| Rejection::new(Context::direct_upload(Some(id.clone())), e);
+
+        let id = Uuid::parse_str(&id)
+            .map_err(|_| BackendError::InvalidId(id.clone()))
+            .map_err(error_handler)?;
+
+        let Environment { logger, db, store, .. } = environment.clone();
+        let logger = Arc::new(logger.new(o!("upload" => format!("{}", id))));
 
-        let mime_type = db
-            .retrieve_mime_type(&audio_format)
+        let upload = db
+            .retrieve_direct_upload(&id)
             .await
-            .map_err(&error_handler)?
-            .ok_or_else(|| error_handler(BackendError::InvalidAudioFormat {
-                format: audio_format,
-            }))?;
-
-        complete_upload(
-                environment.clone(),
-                id,
-                token,
-                email,
-                mime_type,
-                verified_audio,
-                error_handler,
-            )
-                .await?
+            .map_err(error_handler)?
+            .ok_or(BackendError::NonExistentUpload(id))
+            .map_err(error_handler)?;
+
+        debug!(logger, "Loading upload from store...");
+        let audio = store
+            .load(&staging_key(&id))
+            .await
+            .map_err(error_handler)?
+            .ok_or(BackendError::UploadIncomplete(id))
+            .map_err(error_handler)?;
+
+        // the upload is over either way, so that it's only processed
+        // once, and the audio is saved again under the recording's ID
+        db.delete_direct_upload(&id).await.map_err(error_handler)?;
+        delete_object_in_background(logger.clone(), store.clone(), staging_key(&id));
+
+        let token = upload.token;
+        let parent_id = upload.parent_id;
+        let logger = Arc::new(logger.new(o!(
+            "token" => format!("{}", token),
+            "parent_id" => format!("{}", parent_id),
+        )));
+
+        // the signature should prevent this, but not every store checks
+        if audio.len() as u64 > upload.length {
+            release_token_in_background(logger.clone(), db.clone(), token);
+
+            return Err(error_handler(BackendError::UploadTooLong {
+                length: upload.length,
+            })
+            .into());
+        };
+
+        process_upload(environment.clone(), logger, parent_id, upload.metadata, audio).await?
     }
 }
 
 pub async fn children<O: SafeStore>(environment: Environment<O>, parent: String) -> RouteResult {
-    timed! {
+    timed! { environment;
         let error_handler = |e: BackendError| Rejection::new(Context::children(parent.clone()), e);
 
         let id = Uuid::parse_str(&parent)
@@ -210,6 +564,7 @@ pub async fn children<O: SafeStore>(environment: Environment<O>, parent: String)
             .map_err(error_handler)?;
         debug!(environment.logger, "Searching for children..."; "parent" => &parent.to_string());
 
+        ensure_active(environment.db.clone(), &id).await.map_err(error_handler)?;
         let children = environment.db.children(&id).await.map_err(error_handler)?;
         let response = SuccessResponse::Children { parent, children };
 
@@ -218,7 +573,7 @@ pub async fn children<O: SafeStore>(environment: Environment<O>, parent: String)
 }
 
 pub async fn delete<O: SafeStore>(environment: Environment<O>, id: String) -> RouteResult {
-    timed! {
+    timed! { environment;
         let error_handler = |e: BackendError| Rejection::new(Context::delete(id.clone()), e);
 
         let id = Uuid::parse_str(&id)
@@ -226,7 +581,14 @@ pub async fn delete<O: SafeStore>(environment: Environment<O>, id: String) -> Ro
             .map_err(error_handler)?;
         debug!(environment.logger, "Deleting recording..."; "id" => format!("{}", &id));
 
-        environment.store.delete(&id).await.map_err(error_handler)?;
+        let recording = ensure_active(environment.db.clone(), &id)
+            .await
+            .map_err(error_handler)?;
+
+        if let Some(key) = recording.object_key() {
+            environment.store.delete(key).await.map_err(error_handler)?;
+        };
+
         environment
             .db
             .delete(&id)
@@ -241,7 +603,7 @@ pub async fn delete<O: SafeStore>(environment: Environment<O>, id: String) -> Ro
 pub async fn retrieve<O: SafeStore>(environment: Environment<O>, id: String) -> RouteResult {
     use crate::recording::Recording;
 
-    timed! {
+    timed! { environment;
         let error_handler = |e: BackendError| Rejection::new(Context::retrieve(id.clone()), e);
 
         let id = Uuid::parse_str(&id)
@@ -249,24 +611,129 @@ pub async fn retrieve<O: SafeStore>(environment: Environment<O>, id: String) ->
             .map_err(error_handler)?;
         debug!(environment.logger, "Retrieving recording..."; "id" => format!("{}", &id));
 
-        let option = environment.db.retrieve(&id).await.map_err(error_handler)?;
+        let recording = environment
+            .db
+            .retrieve(&id)
+            .await
+            .map_err(error_handler)?
+            .ok_or(BackendError::NonExistentId(id))
+            .map_err(error_handler)?;
+
+        // deleted recordings are still described, unlike in other routes
+        let (recording, status) = match recording {
+            Recording::Active(recording) => {
+                let signed = match recording.object_key() {
+                    Some(key) => environment
+                        .store
+                        .sign_url(key)
+                        .map_err(|source| BackendError::FailedToGenerateUrl { source })
+                        .map_err(error_handler)?,
+                    None => None,
+                };
 
-        match option {
-            Some(recording) => {
-                let status = match recording {
-                    Recording::Active(_) => StatusCode::OK,
-                    Recording::Deleted(_) => StatusCode::GONE,
+                let recording = match signed {
+                    Some(signed) => recording.with_signed_url(signed),
+                    None => recording,
                 };
 
-                with_status(json(&recording), status)
+                (Recording::Active(recording), StatusCode::OK)
             }
-            None => with_status(json(&()), StatusCode::NOT_FOUND),
-        }
+            deleted => (deleted, StatusCode::GONE),
+        };
+
+        with_status(json(&recording), status)
+    }
+}
+
+pub async fn audio<O: SafeStore>(
+    environment: Environment<O>,
+    id: String,
+    range: Option<String>,
+    if_none_match: Option<String>,
+) -> RouteResult {
+    use warp::http::Response;
+    use warp::hyper::Body;
+
+    use crate::recording::Recording;
+    use crate::store::{Fetched, GetConditions};
+
+    timed! { environment;
+        let error_handler = |e: BackendError| Rejection::new(Context::audio(id.clone()), e);
+
+        let id = Uuid::parse_str(&id)
+            .map_err(|_| BackendError::InvalidId(id.clone()))
+            .map_err(error_handler)?;
+        debug!(environment.logger, "Streaming recording..."; "id" => format!("{}", &id));
+
+        let recording = match environment.db.retrieve(&id).await.map_err(error_handler)? {
+            Some(Recording::Active(recording)) => recording,
+            Some(Recording::Deleted(_)) => {
+                return Err(error_handler(BackendError::RecordingDeleted(id)).into())
+            }
+            None => return Err(error_handler(BackendError::NonExistentId(id)).into()),
+        };
+
+        let conditions = GetConditions {
+            range,
+            if_none_match,
+        };
+
+        // a recording whose upload hasn't finished has no audio yet
+        let key = recording
+            .object_key()
+            .ok_or(BackendError::NonExistentId(id))
+            .map_err(error_handler)?;
+
+        let fetched = environment
+            .store
+            .get(key, conditions)
+            .await
+            .map_err(error_handler)?
+            .ok_or(BackendError::NonExistentId(id))
+            .map_err(error_handler)?;
+
+        let response = Response::builder().header("accept-ranges", "bytes");
+
+        let response = match fetched {
+            Fetched::Object(object) => {
+                let status = if object.content_range.is_some() {
+                    StatusCode::PARTIAL_CONTENT
+                } else {
+                    StatusCode::OK
+                };
+
+                let mut response = response
+                    .status(status)
+                    .header("content-type", recording.mime_type().label.as_str());
+
+                if let Some(length) = object.content_length {
+                    response = response.header("content-length", length);
+                }
+
+                if let Some(content_range) = object.content_range {
+                    response = response.header("content-range", content_range);
+                }
+
+                if let Some(etag) = object.etag {
+                    response = response.header("etag", etag);
+                }
+
+                response.body(Body::wrap_stream(object.body))
+            }
+            Fetched::NotModified => response.status(StatusCode::NOT_MODIFIED).body(Body::empty()),
+            Fetched::RangeNotSatisfiable => response
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .body(Body::empty()),
+        };
+
+        response
+            .map_err(BackendError::MalformedResponse)
+            .map_err(error_handler)?
     }
 }
 
 pub async fn random<O: SafeStore>(environment: Environment<O>, count: u8) -> RouteResult {
-    timed! {
+    timed! { environment;
         let count = count as i16;
 
         let error_handler = |e: BackendError| Rejection::new(Context::random(count), e);
@@ -282,30 +749,26 @@ pub async fn random<O: SafeStore>(environment: Environment<O>, count: u8) -> Rou
 }
 
 pub async fn token<O: SafeStore>(environment: Environment<O>, id: Uuid) -> RouteResult {
-    timed! {
+    timed! { environment;
         let error_handler = |e: BackendError| Rejection::new(Context::token(id.to_string()), e);
 
         let token = environment
             .db
             .retrieve_token(&id)
             .await
+            .map_err(error_handler)?
+            .ok_or(BackendError::NonExistentToken(id))
             .map_err(error_handler)?;
 
-        match token {
-            Some(token) => with_status(
-                json(&SuccessResponse::Token {
-                    id: token.id.to_string(),
-                    parent_id: token.parent_id.to_string(),
-                }),
-                StatusCode::OK,
-            ),
-            _ => with_status(json(&()), StatusCode::NOT_FOUND),
-        }
+        json(&SuccessResponse::Token {
+            id: token.id.to_string(),
+            parent_id: token.parent_id.to_string(),
+        })
     }
 }
 
 pub async fn lookup<O: SafeStore>(environment: Environment<O>, key: String) -> RouteResult {
-    timed! {
+    timed! { environment;
         let error_handler = |e: BackendError| Rejection::new(Context::lookup_key(key.clone()), e);
 
         let key = Uuid::parse_str(&key)
@@ -313,19 +776,60 @@ pub async fn lookup<O: SafeStore>(environment: Environment<O>, key: String) -> R
             .map_err(error_handler)?;
         debug!(environment.logger, "Looking up key..."; "key" => format!("{}", key));
 
-        let option = environment
+        let (id, tokens) = environment
             .db
             .lookup_key(&key)
             .await
+            .map_err(error_handler)?
+            .ok_or(BackendError::NonExistentKey(key))
             .map_err(error_handler)?;
 
-        match option {
-            Some((id, tokens)) => with_status(
-                json(&SuccessResponse::Lookup { id, tokens }),
-                StatusCode::OK,
-            ),
-            _ => with_status(json(&()), StatusCode::NOT_FOUND),
-        }
+        json(&SuccessResponse::Lookup { id, tokens })
+    }
+}
+
+pub async fn report<O: SafeStore>(
+    environment: Environment<O>,
+    id: String,
+    remote: Option<SocketAddr>,
+    forwarded_for: Option<String>,
+    report: NewReport,
+) -> RouteResult {
+    use log::o;
+
+    timed! { environment;
+        let error_handler = |e: BackendError| Rejection::new(Context::report(id.clone()), e);
+
+        let id = Uuid::parse_str(&id)
+            .map_err(|_| BackendError::InvalidId(id.clone()))
+            .map_err(error_handler)?;
+
+        let Environment { logger, db, config, .. } = environment;
+
+        let reporter = identify_client(config.trust_forwarded_for, remote, forwarded_for);
+        let logger = logger.new(o!("id" => format!("{}", id), "reporter" => reporter.clone()));
+
+        ensure_active(db.clone(), &id).await.map_err(error_handler)?;
+
+        debug!(logger, "Saving report..."; "reason" => report.reason.as_str());
+        let report_id = db
+            .create_report(&id, &reporter, report, config.reports_per_hour)
+            .await
+            .map_err(error_handler)?;
+
+        if let Some(threshold) = config.reports_to_hide {
+            let reporters = db.count_reporters(&id).await.map_err(error_handler)?;
+
+            if reporters >= i64::from(threshold) {
+                info!(logger, "Hiding recording after {} reports...", reporters);
+                db.hide(&id).await.map_err(error_handler)?;
+            }
+        };
+
+        with_status(
+            json(&SuccessResponse::Report { id: report_id }),
+            StatusCode::CREATED,
+        )
     }
 }
 
@@ -333,23 +837,98 @@ pub async fn availability<O: SafeStore>(
     environment: Environment<O>,
     query: AvailabilityQuery,
 ) -> RouteResult {
-    timed! {
+    timed! { environment;
         let AvailabilityQuery { name } = query;
 
-        let available = environment
+        let error_handler = |e: BackendError| Rejection::new(Context::availability(name.clone()), e);
+
+        let blocked_terms = environment
             .db
-            .check_availability(&name)
+            .retrieve_blocked_terms()
             .await
-            .map_err(|e| Rejection::new(Context::availability(name.clone()), e))?;
+            .map_err(error_handler)?;
 
-        if available {
-            StatusCode::OK
+        // blocked names aren't taken, just never available
+        if blocklist::is_blocked(&blocked_terms, &name) {
+            Box::new(StatusCode::UNPROCESSABLE_ENTITY) as Box<dyn Reply>
+        } else if environment
+            .db
+            .check_availability(&name)
+            .await
+            .map_err(error_handler)?
+        {
+            Box::new(StatusCode::OK)
         } else {
-            StatusCode::FORBIDDEN
+            let candidates =
+                suggestions::candidates(&name, environment.config.metadata_limits.name);
+
+            let mut suggestions = environment
+                .db
+                .filter_available_names(candidates)
+                .await
+                .map_err(error_handler)?;
+            suggestions.truncate(suggestions::MAX_SUGGESTIONS);
+
+            Box::new(with_status(
+                json(&SuccessResponse::Suggestions { suggestions }),
+                StatusCode::FORBIDDEN,
+            ))
         }
     }
 }
 
+pub async fn reserve<O: SafeStore>(
+    environment: Environment<O>,
+    reservation: NewReservation,
+) -> RouteResult {
+    timed! { environment;
+        let name = reservation.name.clone();
+
+        let error_handler = |e: BackendError| Rejection::new(Context::reserve(name.clone()), e);
+
+        let blocked_terms = environment
+            .db
+            .retrieve_blocked_terms()
+            .await
+            .map_err(error_handler)?;
+
+        // there's no point holding a name that can't be uploaded
+        let errors = validation::validate_name(
+            &name,
+            &environment.config.metadata_limits,
+            &blocked_terms,
+        );
+
+        if !errors.is_empty() {
+            return Err(error_handler(BackendError::InvalidMetadata(errors)).into());
+        };
+
+        debug!(environment.logger, "Reserving name..."; "token" => format!("{}", reservation.token));
+        let reservation = environment
+            .db
+            .reserve_name(reservation, environment.config.reservation_duration)
+            .await
+            .map_err(error_handler)?;
+
+        with_status(json(&reservation), StatusCode::CREATED)
+    }
+}
+
+/// Gets the recording unless it doesn’t exist or has been deleted, so
+/// that routes can tell the two apart.
+async fn ensure_active(
+    db: Arc<dyn Db + Send + Sync>,
+    id: &Uuid,
+) -> Result<ActiveRecording, BackendError> {
+    use crate::recording::Recording;
+
+    match db.retrieve(id).await? {
+        Some(Recording::Active(recording)) => Ok(recording),
+        Some(Recording::Deleted(_)) => Err(BackendError::RecordingDeleted(*id)),
+        None => Err(BackendError::NonExistentId(*id)),
+    }
+}
+
 async fn parse_recording_metadata(
     _logger: Arc<Logger>,
     part: Part,
@@ -366,6 +945,25 @@ async fn parse_recording_metadata(
     Ok(upload_metadata)
 }
 
+async fn validate_metadata(
+    db: Arc<dyn Db + Send + Sync>,
+    limits: &MetadataLimits,
+    metadata: &UploadMetadata,
+) -> Result<(), BackendError> {
+    let labels = db
+        .check_labels(metadata.age_id, metadata.gender_id, metadata.category_id)
+        .await?;
+    let blocked_terms = db.retrieve_blocked_terms().await?;
+
+    let errors = validation::validate(metadata, limits, labels, &blocked_terms);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(BackendError::InvalidMetadata(errors))
+    }
+}
+
 async fn lock_token(
     _logger: Arc<Logger>,
     db: Arc<dyn Db + Send + Sync>,
@@ -384,16 +982,47 @@ async fn release_token(
     db.release_token(&token).await
 }
 
+/// Spawns a task to release the token, logging any errors, so that
+/// the request can fail right away.
+fn release_token_in_background(logger: Arc<Logger>, db: Arc<dyn Db + Send + Sync>, token: Uuid) {
+    tokio::spawn(async move {
+        release_token(logger.clone(), db.clone(), token)
+            .await
+            .map_err(|e| {
+                error!(logger, "Failed to release token: {}", e);
+            })
+    });
+}
+
+/// Spawns a task to delete an object from the store, logging any
+/// errors, so that the request doesn’t wait on it.
+fn delete_object_in_background<O: SafeStore + 'static>(
+    logger: Arc<Logger>,
+    store: Arc<environment::VecStore<O>>,
+    key: String,
+) {
+    tokio::spawn(async move {
+        store.delete(&key).await.map_err(|e| {
+            error!(logger, "Failed to delete object: {}", e; "key" => &key);
+        })
+    });
+}
+
+async fn retrieve_upload_session(
+    db: Arc<dyn Db + Send + Sync>,
+    id: &Uuid,
+) -> Result<UploadSession, BackendError> {
+    db.retrieve_upload_session(id)
+        .await?
+        .ok_or(BackendError::NonExistentUpload(*id))
+}
+
 async fn verify_audio(
     _logger: Arc<Logger>,
     checker: Arc<environment::Checker>,
-    audio: Part,
+    audio_data: Vec<u8>,
 ) -> Result<(Vec<u8>, AudioFormat), BackendError> {
-    use crate::io;
-
-    let audio_data = io::part_as_vec(audio)
-        .await
-        .map_err(|_| BackendError::MalformedFormSubmission)?;
+    metrics::record_upload_size(audio_data.len());
 
     // always use the first format
     let formats = checker(&audio_data).map_err(|_| BackendError::MalformedFormSubmission)?;
@@ -409,16 +1038,90 @@ async fn save_recording_metadata(
     db: Arc<dyn Db + Send + Sync>,
     parent_id: &Uuid,
     metadata: UploadMetadata,
-) -> Result<Uuid, BackendError> {
+) -> Result<(Uuid, OffsetDateTime), BackendError> {
     let new_recording = db.insert(parent_id, metadata).await?;
-    let id = new_recording.id();
 
-    Ok(*id)
+    Ok((*new_recording.id(), new_recording.created_at()))
+}
+
+/// Verifies and stores a recording whose token has been locked,
+/// releasing the token if anything goes wrong before it’s saved.
+async fn process_upload<O: SafeStore + 'static>(
+    environment: Environment<O>,
+    logger: Arc<Logger>,
+    parent_id: Uuid,
+    metadata: UploadMetadata,
+    audio: Vec<u8>,
+) -> Result<Box<dyn Reply>, reject::Rejection> {
+    use log::o;
+
+    let Environment {
+        db, checker, trace, ..
+    } = environment.clone();
+    let token = metadata.token;
+
+    let error_handler = |e: BackendError| {
+        release_token_in_background(logger.clone(), db.clone(), token);
+        Rejection::new(Context::upload(None), e)
+    };
+
+    debug!(logger, "Verifying audio contents...");
+    let (verified_audio, audio_format) = in_span(
+        &trace,
+        "verify",
+        verify_audio(logger.clone(), checker, audio),
+    )
+    .await
+    .map_err(&error_handler)?;
+
+    // TODO retry in case ID already exists
+    debug!(logger, "Writing metadata to database...");
+    let email = metadata.email.clone(); // save for later
+    let (id, created_at) = in_span(
+        &trace,
+        "insert",
+        save_recording_metadata(logger.clone(), db.clone(), &parent_id, metadata),
+    )
+    .await
+    .map_err(&error_handler)?;
+    let id_as_str = format!("{}", id);
+    let logger = Arc::new(logger.new(o!("id" => id_as_str.clone())));
+
+    let error_handler = |e: BackendError| {
+        // TODO delete row from DB
+        Rejection::new(Context::upload(Some(id_as_str.clone())), e)
+    };
+
+    // should this punt to a queue? is that necessary?
+    debug!(logger, "Saving recording to store...");
+
+    let mime_type = db
+        .retrieve_mime_type(&audio_format)
+        .await
+        .map_err(&error_handler)?
+        .ok_or_else(|| {
+            error_handler(BackendError::InvalidAudioFormat {
+                format: audio_format,
+            })
+        })?;
+
+    complete_upload(
+        environment.clone(),
+        id,
+        created_at,
+        token,
+        email,
+        mime_type,
+        verified_audio,
+        error_handler,
+    )
+    .await
 }
 
 async fn complete_upload<O: SafeStore + 'static>(
     environment: Environment<O>,
     id: Uuid,
+    created_at: OffsetDateTime,
     token: Uuid,
     email: Option<String>,
     mime_type: MimeType,
@@ -428,31 +1131,52 @@ async fn complete_upload<O: SafeStore + 'static>(
     let logger = environment.logger.clone();
     let db = environment.db.clone();
     let store = environment.store.clone();
+    let trace = &environment.trace;
 
-    store
-        .save(&id, mime_type.essence.clone(), verified_audio)
-        .await
-        .map_err(&error_handler)?;
+    let key = environment
+        .keys
+        .render(&id, created_at, &mime_type.extension);
 
-    debug!(logger, "Updating recording URL...");
-    update_recording_url(logger.clone(), db.clone(), store.clone(), &id, mime_type)
-        .await
-        .map_err(&error_handler)?;
+    in_span(
+        trace,
+        "store",
+        store.save(&key, mime_type.essence.clone(), verified_audio),
+    )
+    .await
+    .map_err(&error_handler)?;
+
+    debug!(logger, "Updating recording URL..."; "key" => &key);
+    update_recording_url(
+        logger.clone(),
+        db.clone(),
+        store.clone(),
+        &id,
+        &key,
+        mime_type,
+    )
+    .await
+    .map_err(&error_handler)?;
 
     debug!(logger, "Removing parent token...");
     db.remove_token(&token).await.map_err(&error_handler)?;
 
     debug!(logger, "Creating child tokens...");
-    let tokens = create_tokens(
-        logger.clone(),
-        db.clone(),
-        id,
-        environment.config.tokens_per_recording,
+    let tokens = in_span(
+        trace,
+        "tokens",
+        create_tokens(
+            logger.clone(),
+            db.clone(),
+            id,
+            environment.config.tokens_per_recording,
+        ),
     )
     .await
     .map_err(&error_handler)?;
 
-    let key = db.create_key(&id, email).await.map_err(&error_handler)?;
+    let key = in_span(trace, "key", db.create_key(&id, email))
+        .await
+        .map_err(&error_handler)?;
 
     let id_as_str = format!("{}", id);
 
@@ -474,14 +1198,16 @@ async fn update_recording_url<O>(
     _logger: Arc<Logger>,
     db: Arc<dyn Db + Send + Sync>,
     store: Arc<environment::VecStore<O>>,
-    key: &Uuid,
+    id: &Uuid,
+    key: &str,
     mime_type: MimeType,
-) -> Result<Url, BackendError> {
+) -> Result<Option<Url>, BackendError> {
     let url = store
-        .get_url(&key)
+        .get_url(key)
         .map_err(|e| BackendError::FailedToGenerateUrl { source: e })?;
 
-    db.update_url(key, &url, mime_type.clone()).await?;
+    db.update_url(id, key, url.as_ref(), mime_type.clone())
+        .await?;
 
     Ok(url)
 }
@@ -503,6 +1229,24 @@ async fn create_tokens(
     Ok(tokens)
 }
 
+/// Identifies the client making a request, for throttling purposes.
+/// `X-Forwarded-For` can be spoofed by anyone, so it’s only consulted
+/// when the HTTP gateway is known to overwrite it.
+fn identify_client(
+    trust_forwarded_for: bool,
+    remote: Option<SocketAddr>,
+    forwarded_for: Option<String>,
+) -> String {
+    let forwarded = forwarded_for
+        .filter(|_| trust_forwarded_for)
+        .and_then(|header| header.split(',').next().map(|s| s.trim().to_owned()))
+        .filter(|s| !s.is_empty());
+
+    forwarded
+        .or_else(|| remote.map(|address| address.ip().to_string()))
+        .unwrap_or_else(|| "unknown".to_owned())
+}
+
 fn format_server_timing(seconds: Duration) -> String {
     format!("handler;dur={}", seconds.as_secs_f64() * 1000.0)
 }
@@ -4,3 +4,8 @@ use serde::Deserialize;
 pub struct AvailabilityQuery {
     pub name: String,
 }
+
+#[derive(Deserialize)]
+pub struct LanguageQuery {
+    pub lang: Option<String>,
+}
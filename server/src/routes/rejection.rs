@@ -1,7 +1,8 @@
 use serde::Serialize;
 use warp::reject;
 
-use crate::errors::BackendError;
+use crate::errors::{BackendError, ErrorDetails};
+use crate::request_id;
 
 #[derive(Debug)]
 pub struct Rejection {
@@ -18,6 +19,9 @@ impl Rejection {
         FlattenedRejection {
             context: self.context.clone(),
             message: format!("{}", self.error),
+            code: self.error.code(),
+            details: self.error.details(),
+            request_id: request_id::current(),
         }
     }
 }
@@ -29,23 +33,55 @@ pub struct FlattenedRejection {
     #[serde(flatten)]
     pub(crate) context: Context,
     pub(crate) message: String,
+
+    /// A stable code for the error, unlike the message.
+    pub(crate) code: &'static str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) details: Option<ErrorDetails>,
+
+    /// The ID of the failed request, for quoting in bug reports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) request_id: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize)]
 #[serde(untagged)]
 pub enum Context {
     Ages,
+    Audio { id: String },
+    Authentication,
     Availability { name: String },
+    BlockTerm { term: String },
+    BlockedTerm { id: i32 },
+    Blocklist,
     Categories,
     Children { parent: String },
     Count,
+    CreateFormat { container: String, codec: String },
+    CreateLabel { kind: String },
     Delete { id: String },
+    DirectUpload { upload: Option<String> },
+    Format { id: i16 },
     Formats,
     Genders,
+    Hide { id: String },
+    Labels { kind: String },
     LookupKey { token: String },
+    Purge { id: String },
     Random { count: i16 },
+    Recent { count: i16 },
+    ReorderLabels { kind: String },
+    Report { id: String },
+    Reports,
+    Reserve { name: String },
+    ResolveReport { id: String },
+    Restore { id: String },
+    ResumableUpload { upload: Option<String> },
     Retrieve { id: String },
     Token { id: String },
+    Translation { kind: String, id: i16, tag: String },
+    UpdateLabel { kind: String, id: i16 },
     Upload { id: Option<String> },
 }
 
@@ -54,10 +90,30 @@ impl Context {
         Context::Ages
     }
 
+    pub fn audio(id: String) -> Context {
+        Context::Audio { id }
+    }
+
+    pub fn authentication() -> Context {
+        Context::Authentication
+    }
+
     pub fn availability(name: String) -> Context {
         Context::Availability { name }
     }
 
+    pub fn block_term(term: String) -> Context {
+        Context::BlockTerm { term }
+    }
+
+    pub fn blocked_term(id: i32) -> Context {
+        Context::BlockedTerm { id }
+    }
+
+    pub fn blocklist() -> Context {
+        Context::Blocklist
+    }
+
     pub fn categories() -> Context {
         Context::Categories
     }
@@ -70,10 +126,26 @@ impl Context {
         Context::Count
     }
 
+    pub fn create_format(container: String, codec: String) -> Context {
+        Context::CreateFormat { container, codec }
+    }
+
+    pub fn create_label(kind: String) -> Context {
+        Context::CreateLabel { kind }
+    }
+
     pub fn delete(id: String) -> Context {
         Context::Delete { id }
     }
 
+    pub fn direct_upload(upload: Option<String>) -> Context {
+        Context::DirectUpload { upload }
+    }
+
+    pub fn format(id: i16) -> Context {
+        Context::Format { id }
+    }
+
     pub fn formats() -> Context {
         Context::Formats
     }
@@ -82,14 +154,58 @@ impl Context {
         Context::Genders
     }
 
+    pub fn hide(id: String) -> Context {
+        Context::Hide { id }
+    }
+
+    pub fn labels(kind: String) -> Context {
+        Context::Labels { kind }
+    }
+
     pub fn lookup_key(token: String) -> Context {
         Context::LookupKey { token }
     }
 
+    pub fn purge(id: String) -> Context {
+        Context::Purge { id }
+    }
+
     pub fn random(count: i16) -> Context {
         Context::Random { count }
     }
 
+    pub fn recent(count: i16) -> Context {
+        Context::Recent { count }
+    }
+
+    pub fn reorder_labels(kind: String) -> Context {
+        Context::ReorderLabels { kind }
+    }
+
+    pub fn report(id: String) -> Context {
+        Context::Report { id }
+    }
+
+    pub fn reports() -> Context {
+        Context::Reports
+    }
+
+    pub fn reserve(name: String) -> Context {
+        Context::Reserve { name }
+    }
+
+    pub fn resolve_report(id: String) -> Context {
+        Context::ResolveReport { id }
+    }
+
+    pub fn restore(id: String) -> Context {
+        Context::Restore { id }
+    }
+
+    pub fn resumable_upload(upload: Option<String>) -> Context {
+        Context::ResumableUpload { upload }
+    }
+
     pub fn retrieve(id: String) -> Context {
         Context::Retrieve { id }
     }
@@ -98,6 +214,14 @@ impl Context {
         Context::Token { id }
     }
 
+    pub fn translation(kind: String, id: i16, tag: String) -> Context {
+        Context::Translation { kind, id, tag }
+    }
+
+    pub fn update_label(kind: String, id: i16) -> Context {
+        Context::UpdateLabel { kind, id }
+    }
+
     pub fn upload(id: Option<String>) -> Context {
         Context::Upload { id }
     }
@@ -1,21 +1,34 @@
+use std::collections::BTreeMap;
+
 use serde::Serialize;
 use uuid::Uuid;
 
-use crate::recording::{ChildRecording, PartialRecording};
+use crate::label::Id;
+use crate::recording::{ChildRecording, ModeratedRecording, PartialRecording};
+use crate::report::Report;
 
 #[derive(Debug, Serialize)]
 #[serde(untagged)]
 pub enum SuccessResponse<'a> {
+    BlockedTerm {
+        id: i32,
+    },
     Children {
         parent: String,
         children: Vec<ChildRecording>,
     },
     Count(i64),
+    Format {
+        id: Id,
+    },
     Healthz {
         revision: Option<&'a str>,
         timestamp: Option<&'a str>,
         version: &'a str,
     },
+    Label {
+        id: Id,
+    },
     Lookup {
         id: Uuid,
         tokens: Vec<Uuid>,
@@ -23,6 +36,22 @@ pub enum SuccessResponse<'a> {
     Random {
         recordings: Vec<PartialRecording>,
     },
+    Readyz {
+        ready: bool,
+        checks: BTreeMap<&'a str, Check>,
+    },
+    Recent {
+        recordings: Vec<ModeratedRecording>,
+    },
+    Report {
+        id: Uuid,
+    },
+    Reports {
+        reports: Vec<Report>,
+    },
+    Suggestions {
+        suggestions: Vec<String>,
+    },
     Token {
         id: String,
         parent_id: String,
@@ -34,3 +63,28 @@ pub enum SuccessResponse<'a> {
         key: Option<Uuid>,
     },
 }
+
+/// The outcome of checking a single dependency.
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub(crate) ok: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+impl Check {
+    pub fn passed() -> Self {
+        Check {
+            ok: true,
+            error: None,
+        }
+    }
+
+    pub fn failed(error: String) -> Self {
+        Check {
+            ok: false,
+            error: Some(error),
+        }
+    }
+}
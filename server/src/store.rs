@@ -1,11 +1,31 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io;
 use std::sync::Arc;
+use std::time::Duration;
 
+use bytes::Bytes;
 use futures::future::{BoxFuture, FutureExt};
-use rusoto_s3::{DeleteObjectRequest, PutObjectRequest, S3Client, StreamingBody, S3};
+use futures::stream::BoxStream;
+use rusoto_core::{HttpDispatchError, Region, RusotoError};
+use rusoto_credential::AwsCredentials;
+use rusoto_s3::{
+    CopyObjectRequest, DeleteObjectRequest, GetObjectRequest, HeadBucketRequest,
+    ListObjectsV2Request, PutObjectRequest, S3Client, StreamingBody, S3,
+};
+use serde::Serialize;
+use time::{OffsetDateTime, PrimitiveDateTime};
 use url::{ParseError, Url};
-use uuid::Uuid;
 
 use crate::errors::BackendError;
+use crate::metrics;
+use crate::retry::{CircuitBreaker, RetryPolicy};
+
+/// How long URLs to objects in a private store work for if not
+/// otherwise configured.
+pub const DEFAULT_SIGNED_URL_DURATION: Duration = Duration::from_secs(60 * 60);
+
+const PRIVATE_ACL: &str = "private";
 
 pub trait Store: Send + Sync {
     /// The type of successful result.
@@ -14,54 +34,188 @@ pub trait Store: Send + Sync {
     /// The type of raw data.
     type Raw;
 
+    /// Checks that the store is reachable and usable.
+    fn check(&self) -> BoxFuture<Result<(), BackendError>>;
+
+    /// Copies an object to another key, keeping its content type.
+    fn copy(&self, from: &str, to: &str) -> BoxFuture<Result<(), BackendError>>;
+
     /// Deletes the given object.
-    fn delete(&self, key: &Uuid) -> BoxFuture<Result<(), BackendError>>;
+    fn delete(&self, key: &str) -> BoxFuture<Result<(), BackendError>>;
+
+    /// Streams the given object, or the part of it asked for, if it
+    /// exists.
+    fn get(
+        &self,
+        key: &str,
+        conditions: GetConditions,
+    ) -> BoxFuture<Result<Option<Fetched>, BackendError>>;
+
+    /// Gets the permanent URL for the given object, unless the store
+    /// is private.
+    fn get_url(&self, key: &str) -> Result<Option<Url>, ParseError>;
 
-    /// Gets the URL for the given object.
-    fn get_url(&self, key: &Uuid) -> Result<Url, ParseError>;
+    /// Lists every object whose key starts with the given prefix.
+    fn list(&self, prefix: &str) -> BoxFuture<Result<Vec<StoredObject>, BackendError>>;
+
+    /// Loads the given object, if it exists.
+    fn load(&self, key: &str) -> BoxFuture<Result<Option<Self::Raw>, BackendError>>;
+
+    /// Signs a request for a client to save an object of the given
+    /// type and length under the given key itself, if the store can be
+    /// reached by clients. The object is private whatever the store's
+    /// access, as it's only kept until it's been processed.
+    fn presign_save(
+        &self,
+        key: &str,
+        content_type: &str,
+        length: u64,
+        expires_in: Duration,
+    ) -> Result<Option<PresignedRequest>, ParseError>;
+
+    /// Signs a URL for the given object that only works for a while,
+    /// if the store is private.
+    fn sign_url(&self, key: &str) -> Result<Option<SignedUrl>, ParseError>;
 
     /// Saves the given data under the given key.
     fn save(
         &self,
-        key: &Uuid,
+        key: &str,
         content_type: String,
         raw: Self::Raw,
     ) -> BoxFuture<Result<Self::Output, BackendError>>;
 }
 
+/// A request signed for a client to make against the store directly.
+#[derive(Clone, Debug, Serialize)]
+pub struct PresignedRequest {
+    pub url: Url,
+
+    /// The headers the client must send exactly as given, as they’re
+    /// part of the signature.
+    pub headers: BTreeMap<&'static str, String>,
+}
+
+/// What to send of an object, as given in the headers of a request
+/// for it.
+#[derive(Clone, Debug, Default)]
+pub struct GetConditions {
+    /// The value of the `Range` header.
+    pub range: Option<String>,
+
+    /// The value of the `If-None-Match` header.
+    pub if_none_match: Option<String>,
+}
+
+/// The result of streaming an object.
+pub enum Fetched {
+    /// The object, or the part of it asked for if `content_range` is
+    /// set.
+    Object(ObjectStream),
+
+    /// The object matches the entity tag the client already has.
+    NotModified,
+
+    /// The range asked for lies outside the object.
+    RangeNotSatisfiable,
+}
+
+/// An object being streamed from a store.
+pub struct ObjectStream {
+    pub body: BoxStream<'static, Result<Bytes, io::Error>>,
+    pub content_length: Option<u64>,
+    pub content_range: Option<String>,
+    pub etag: Option<String>,
+}
+
+/// An object found when listing a store.
+#[derive(Clone, Debug)]
+pub struct StoredObject {
+    pub key: String,
+
+    /// When the object was last saved, if the store said so in a form
+    /// that could be understood.
+    pub last_modified: Option<OffsetDateTime>,
+}
+
+/// A URL to an object that stops working at some point.
+#[derive(Clone, Debug)]
+pub struct SignedUrl {
+    pub url: Url,
+    pub expires_at: OffsetDateTime,
+}
+
+/// Who can read the objects in a store.
+#[derive(Clone, Debug)]
+pub enum Access {
+    /// Anyone, at a permanent URL under the base URL.
+    Public { acl: String, base_url: Url },
+
+    /// Only those given a URL signed for the given time.
+    Private { url_duration: Duration },
+}
+
+impl Access {
+    fn acl(&self) -> String {
+        match self {
+            Access::Public { acl, .. } => acl.clone(),
+            Access::Private { .. } => PRIVATE_ACL.to_owned(),
+        }
+    }
+}
+
 /// A store that saves its data to S3.
 pub struct S3Store {
     client: Arc<S3Client>,
-    acl: String,
     bucket: String,
     cache_control: String,
-    base_url: Url,
+    access: Access,
+
+    // presigning happens locally, so it needs these separately from
+    // the client
+    region: Region,
+    credentials: AwsCredentials,
+
+    retry: RetryPolicy,
+    breaker: CircuitBreaker,
 }
 
 impl S3Store {
     /// Creates a new instance.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         client: Arc<S3Client>,
-        acl: String,
         bucket: String,
         cache_control: String,
-        base_url: Url,
+        access: Access,
+        region: Region,
+        credentials: AwsCredentials,
+        retry: RetryPolicy,
+        breaker: CircuitBreaker,
     ) -> Self {
         Self {
             client,
-            acl,
             bucket,
             cache_control,
-            base_url,
+            access,
+            region,
+            credentials,
+            retry,
+            breaker,
         }
     }
 
     pub fn from_env() -> Result<Self, rusoto_core::request::TlsError> {
+        use std::env;
+
         use rusoto_core::request::HttpClient;
-        use rusoto_core::Region;
         use rusoto_credential::StaticProvider;
 
         use crate::config::get_variable;
+        use crate::retry::{
+            DEFAULT_BREAKER_COOLDOWN, DEFAULT_BREAKER_THRESHOLD, DEFAULT_CALL_TIMEOUT,
+            DEFAULT_MIN_TRANSFER_RATE, DEFAULT_RETRIES, DEFAULT_RETRY_DELAY,
+        };
 
         let access_key = get_variable("S3_ACCESS_KEY");
         let secret_access_key = get_variable("S3_SECRET_ACCESS_KEY");
@@ -72,18 +226,86 @@ impl S3Store {
         };
 
         let bucket = get_variable("S3_BUCKET_NAME");
-        let acl = get_variable("BACKEND_S3_ACL");
         let cache_control = get_variable("BACKEND_S3_CACHE_CONTROL");
 
+        let access = if env::var("BACKEND_S3_PRIVATE").map_or(false, |v| v == "1") {
+            Access::Private {
+                url_duration: env::var("BACKEND_S3_URL_SECONDS")
+                    .ok()
+                    .map(|v| {
+                        Duration::from_secs(v.parse().expect("parse BACKEND_S3_URL_SECONDS as u64"))
+                    })
+                    .unwrap_or(DEFAULT_SIGNED_URL_DURATION),
+            }
+        } else {
+            Access::Public {
+                acl: get_variable("BACKEND_S3_ACL"),
+                base_url: Url::parse(&get_variable("S3_BASE_URL")).expect("parse S3_BASE_URL"),
+            }
+        };
+
+        let retry = RetryPolicy {
+            retries: env::var("BACKEND_S3_RETRIES")
+                .ok()
+                .map(|v| v.parse().expect("parse BACKEND_S3_RETRIES as u32"))
+                .unwrap_or(DEFAULT_RETRIES),
+            delay: env::var("BACKEND_S3_RETRY_DELAY_MS")
+                .ok()
+                .map(|v| {
+                    Duration::from_millis(
+                        v.parse().expect("parse BACKEND_S3_RETRY_DELAY_MS as u64"),
+                    )
+                })
+                .unwrap_or(DEFAULT_RETRY_DELAY),
+            timeout: env::var("BACKEND_S3_TIMEOUT_SECONDS")
+                .ok()
+                .map(|v| {
+                    Duration::from_secs(v.parse().expect("parse BACKEND_S3_TIMEOUT_SECONDS as u64"))
+                })
+                .unwrap_or(DEFAULT_CALL_TIMEOUT),
+            min_transfer_rate: env::var("BACKEND_S3_MIN_TRANSFER_RATE")
+                .ok()
+                .map(|v| {
+                    v.parse()
+                        .expect("parse BACKEND_S3_MIN_TRANSFER_RATE as u64")
+                })
+                .unwrap_or(DEFAULT_MIN_TRANSFER_RATE),
+        };
+
+        let breaker = CircuitBreaker::new(
+            env::var("BACKEND_S3_BREAKER_THRESHOLD")
+                .ok()
+                .map(|v| {
+                    v.parse()
+                        .expect("parse BACKEND_S3_BREAKER_THRESHOLD as u32")
+                })
+                .unwrap_or(DEFAULT_BREAKER_THRESHOLD),
+            env::var("BACKEND_S3_BREAKER_SECONDS")
+                .ok()
+                .map(|v| {
+                    Duration::from_secs(v.parse().expect("parse BACKEND_S3_BREAKER_SECONDS as u64"))
+                })
+                .unwrap_or(DEFAULT_BREAKER_COOLDOWN),
+        );
+
+        let credentials = AwsCredentials::new(access_key, secret_access_key, None, None);
+
         let client = Arc::new(S3Client::new_with(
             HttpClient::new()?,
-            StaticProvider::new_minimal(access_key, secret_access_key),
-            region,
+            StaticProvider::from(credentials.clone()),
+            region.clone(),
         ));
 
-        let base_url = Url::parse(&get_variable("S3_BASE_URL")).expect("parse S3_BASE_URL");
-
-        Ok(S3Store::new(client, acl, bucket, cache_control, base_url))
+        Ok(S3Store::new(
+            client,
+            bucket,
+            cache_control,
+            access,
+            region,
+            credentials,
+            retry,
+            breaker,
+        ))
     }
 }
 
@@ -91,63 +313,636 @@ impl Store for S3Store {
     type Output = ();
     type Raw = Vec<u8>;
 
-    fn delete<'a>(&self, key: &'a Uuid) -> BoxFuture<Result<(), BackendError>> {
-        delete(self, *key).boxed()
+    fn check(&self) -> BoxFuture<Result<(), BackendError>> {
+        check(self).boxed()
+    }
+
+    fn copy(&self, from: &str, to: &str) -> BoxFuture<Result<(), BackendError>> {
+        copy(self, from.to_owned(), to.to_owned()).boxed()
     }
 
-    fn get_url(&self, key: &Uuid) -> Result<Url, ParseError> {
-        self.base_url.join(&key.to_string())
+    fn delete<'a>(&self, key: &'a str) -> BoxFuture<Result<(), BackendError>> {
+        delete(self, key.to_owned()).boxed()
+    }
+
+    fn get(
+        &self,
+        key: &str,
+        conditions: GetConditions,
+    ) -> BoxFuture<Result<Option<Fetched>, BackendError>> {
+        stream(self, key.to_owned(), conditions).boxed()
+    }
+
+    fn get_url(&self, key: &str) -> Result<Option<Url>, ParseError> {
+        match &self.access {
+            Access::Public { base_url, .. } => base_url.join(key).map(Some),
+            Access::Private { .. } => Ok(None),
+        }
+    }
+
+    fn list(&self, prefix: &str) -> BoxFuture<Result<Vec<StoredObject>, BackendError>> {
+        list(self, prefix.to_owned()).boxed()
+    }
+
+    fn load(&self, key: &str) -> BoxFuture<Result<Option<Vec<u8>>, BackendError>> {
+        download(self, key.to_owned()).boxed()
+    }
+
+    fn presign_save(
+        &self,
+        key: &str,
+        content_type: &str,
+        length: u64,
+        expires_in: Duration,
+    ) -> Result<Option<PresignedRequest>, ParseError> {
+        use std::convert::TryFrom;
+
+        use rusoto_s3::util::{PreSignedRequest, PreSignedRequestOption};
+
+        let len = i64::try_from(length).expect("length must be within range of i64");
+
+        let request = PutObjectRequest {
+            acl: Some(PRIVATE_ACL.to_owned()),
+            bucket: self.bucket.clone(),
+            cache_control: Some(self.cache_control.clone()),
+            content_length: Some(len),
+            content_type: Some(content_type.to_owned()),
+            key: key.to_owned(),
+            ..Default::default()
+        };
+
+        let url = request.get_presigned_url(
+            &self.region,
+            &self.credentials,
+            &PreSignedRequestOption { expires_in },
+        );
+
+        let mut headers = BTreeMap::new();
+        headers.insert("cache-control", self.cache_control.clone());
+        headers.insert("content-length", length.to_string());
+        headers.insert("content-type", content_type.to_owned());
+        headers.insert("x-amz-acl", PRIVATE_ACL.to_owned());
+
+        Ok(Some(PresignedRequest {
+            url: Url::parse(&url)?,
+            headers,
+        }))
+    }
+
+    fn sign_url(&self, key: &str) -> Result<Option<SignedUrl>, ParseError> {
+        use rusoto_s3::util::{PreSignedRequest, PreSignedRequestOption};
+
+        let url_duration = match self.access {
+            Access::Public { .. } => return Ok(None),
+            Access::Private { url_duration } => url_duration,
+        };
+
+        let request = GetObjectRequest {
+            bucket: self.bucket.clone(),
+            key: key.to_owned(),
+            ..Default::default()
+        };
+
+        let expires_at = OffsetDateTime::now_utc() + url_duration;
+        let url = request.get_presigned_url(
+            &self.region,
+            &self.credentials,
+            &PreSignedRequestOption {
+                expires_in: url_duration,
+            },
+        );
+
+        Ok(Some(SignedUrl {
+            url: Url::parse(&url)?,
+            expires_at,
+        }))
     }
 
     fn save<'a>(
         &self,
-        key: &Uuid,
+        key: &str,
         content_type: String,
         raw: Vec<u8>,
     ) -> BoxFuture<Result<(), BackendError>> {
-        upload(self, *key, content_type, raw).boxed()
+        upload(self, key.to_owned(), content_type, raw).boxed()
+    }
+}
+
+/// Makes a call to S3, retrying errors that may go away by themselves
+/// and giving up straight away while the circuit breaker is open. The
+/// outer error is only for the latter, so that callers can still pick
+/// apart whatever S3 returned.
+async fn call<T, E, F, Fut>(
+    store: &S3Store,
+    operation: &'static str,
+    timeout: Duration,
+    make_call: F,
+) -> Result<Result<T, RusotoError<E>>, BackendError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, RusotoError<E>>>,
+{
+    if !store.breaker.allow() {
+        return Err(BackendError::StoreUnavailable);
+    }
+
+    let mut retry = 0;
+
+    loop {
+        let timer = metrics::time_store(operation);
+        let result = time_out(timeout, make_call()).await;
+        drop(timer);
+
+        match result {
+            Err(e) if is_retryable(&e) && retry < store.retry.retries => {
+                metrics::record_store_retry(operation);
+                tokio::time::sleep(store.retry.delay_before(retry)).await;
+                retry += 1;
+            }
+            Err(e) if is_retryable(&e) => {
+                store.breaker.record_failure();
+                return Ok(Err(e));
+            }
+            result => {
+                store.breaker.record_success();
+                return Ok(result);
+            }
+        }
     }
 }
 
-async fn delete(store: &S3Store, key: Uuid) -> Result<(), BackendError> {
-    let request = DeleteObjectRequest {
+/// Fails a call to S3 that takes too long as if the connection had
+/// dropped.
+async fn time_out<T, E>(
+    timeout: Duration,
+    call: impl Future<Output = Result<T, RusotoError<E>>>,
+) -> Result<T, RusotoError<E>> {
+    match tokio::time::timeout(timeout, call).await {
+        Ok(result) => result,
+        Err(_) => Err(RusotoError::HttpDispatch(HttpDispatchError::new(format!(
+            "timed out after {:?}",
+            timeout
+        )))),
+    }
+}
+
+/// Checks whether an error is down to S3 or the connection to it,
+/// rather than to the request.
+fn is_retryable<E>(error: &RusotoError<E>) -> bool {
+    match error {
+        RusotoError::HttpDispatch(_) => true,
+        RusotoError::Unknown(response) => {
+            response.status.is_server_error() || response.status.as_u16() == 429
+        }
+        _ => false,
+    }
+}
+
+async fn check(store: &S3Store) -> Result<(), BackendError> {
+    let make_request = || HeadBucketRequest {
+        bucket: store.bucket.clone(),
+        ..Default::default()
+    };
+
+    // a bucket answering HEAD requests says little about whether
+    // uploads go through, so checks are made once, and neither close
+    // the circuit breaker nor use up its trial call
+    if store.breaker.is_open() {
+        return Err(BackendError::StoreUnavailable);
+    }
+
+    let timer = metrics::time_store("check");
+    let result = time_out(
+        store.retry.timeout,
+        store.client.head_bucket(make_request()),
+    )
+    .await;
+    drop(timer);
+
+    result.map_err(|source| BackendError::StoreCheckFailed { source })
+}
+
+async fn copy(store: &S3Store, from: String, to: String) -> Result<(), BackendError> {
+    // the ACL isn't copied along with the object
+    let make_request = || CopyObjectRequest {
+        acl: Some(store.access.acl()),
+        bucket: store.bucket.clone(),
+        copy_source: format!("{}/{}", store.bucket, from),
+        key: to.clone(),
+        ..Default::default()
+    };
+
+    let result = call(store, "copy", store.retry.timeout, || {
+        store.client.copy_object(make_request())
+    })
+    .await?;
+
+    result
+        .map(|_| ())
+        .map_err(|source| BackendError::StoreCopyFailed { source })
+}
+
+async fn delete(store: &S3Store, key: String) -> Result<(), BackendError> {
+    let make_request = || DeleteObjectRequest {
         bucket: store.bucket.clone(),
-        key: key.to_string(),
+        key: key.clone(),
         ..Default::default()
     };
 
-    let result = store.client.delete_object(request).await;
+    let result = call(store, "delete", store.retry.timeout, || {
+        store.client.delete_object(make_request())
+    })
+    .await?;
 
     result
         .map(|_| ())
         .map_err(|source| BackendError::StoreDeleteFailed { source })
 }
 
+async fn list(store: &S3Store, prefix: String) -> Result<Vec<StoredObject>, BackendError> {
+    let mut objects = vec![];
+    let mut continuation_token = None;
+
+    loop {
+        let make_request = || ListObjectsV2Request {
+            bucket: store.bucket.clone(),
+            prefix: Some(prefix.clone()),
+            continuation_token: continuation_token.clone(),
+            ..Default::default()
+        };
+
+        let result = call(store, "list", store.retry.timeout, || {
+            store.client.list_objects_v2(make_request())
+        })
+        .await?;
+
+        let output = result.map_err(|source| BackendError::StoreListFailed { source })?;
+
+        for object in output.contents.unwrap_or_default() {
+            if let Some(key) = object.key {
+                objects.push(StoredObject {
+                    key,
+                    last_modified: object
+                        .last_modified
+                        .as_deref()
+                        .and_then(parse_last_modified),
+                });
+            }
+        }
+
+        continuation_token = match output.next_continuation_token {
+            Some(token) if output.is_truncated == Some(true) => Some(token),
+            _ => break,
+        };
+    }
+
+    Ok(objects)
+}
+
+/// Parses a timestamp like `2026-10-18T22:00:00.000Z`, ignoring the
+/// fractional seconds.
+fn parse_last_modified(timestamp: &str) -> Option<OffsetDateTime> {
+    let seconds = timestamp.get(..19)?;
+
+    PrimitiveDateTime::parse(seconds, "%Y-%m-%dT%H:%M:%S")
+        .ok()
+        .map(PrimitiveDateTime::assume_utc)
+}
+
+async fn download(store: &S3Store, key: String) -> Result<Option<Vec<u8>>, BackendError> {
+    use rusoto_s3::GetObjectError;
+    use tokio::io::AsyncReadExt;
+
+    let make_request = || GetObjectRequest {
+        bucket: store.bucket.clone(),
+        key: key.clone(),
+        ..Default::default()
+    };
+
+    let result = call(store, "get", store.retry.timeout, || {
+        store.client.get_object(make_request())
+    })
+    .await?;
+
+    let output = match result {
+        Ok(output) => output,
+        Err(RusotoError::Service(GetObjectError::NoSuchKey(_))) => return Ok(None),
+        Err(source) => return Err(BackendError::StoreLoadFailed { source }),
+    };
+
+    let mut raw = vec![];
+
+    // the call only covers the response starting, so the body gets a
+    // timeout of its own
+    if let Some(body) = output.body {
+        let length = output.content_length.unwrap_or(0).max(0) as u64;
+        let timeout = store.retry.transfer_timeout(length);
+        let read = body.into_async_read().read_to_end(&mut raw);
+
+        match tokio::time::timeout(timeout, read).await {
+            Ok(result) => result.map(|_| ()),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("timed out after {:?}", timeout),
+            )),
+        }
+        .map_err(BackendError::StoreLoadInterrupted)?;
+    }
+
+    Ok(Some(raw))
+}
+
+async fn stream(
+    store: &S3Store,
+    key: String,
+    conditions: GetConditions,
+) -> Result<Option<Fetched>, BackendError> {
+    use futures::StreamExt;
+    use rusoto_s3::GetObjectError;
+
+    let make_request = || GetObjectRequest {
+        bucket: store.bucket.clone(),
+        key: key.clone(),
+        range: conditions.range.clone(),
+        if_none_match: conditions.if_none_match.clone(),
+        ..Default::default()
+    };
+
+    let result = call(store, "get", store.retry.timeout, || {
+        store.client.get_object(make_request())
+    })
+    .await?;
+
+    // conditional and partial requests that S3 turns down come back
+    // as errors with nothing but a status code
+    let output = match result {
+        Ok(output) => output,
+        Err(RusotoError::Service(GetObjectError::NoSuchKey(_))) => return Ok(None),
+        Err(RusotoError::Unknown(response)) if response.status.as_u16() == 304 => {
+            return Ok(Some(Fetched::NotModified))
+        }
+        Err(RusotoError::Unknown(response)) if response.status.as_u16() == 416 => {
+            return Ok(Some(Fetched::RangeNotSatisfiable))
+        }
+        Err(source) => return Err(BackendError::StoreLoadFailed { source }),
+    };
+
+    let body = match output.body {
+        Some(body) => body.boxed(),
+        None => futures::stream::empty().boxed(),
+    };
+
+    Ok(Some(Fetched::Object(ObjectStream {
+        body,
+        content_length: output.content_length.map(|length| length as u64),
+        content_range: output.content_range,
+        etag: output.e_tag,
+    })))
+}
+
 async fn upload(
     store: &S3Store,
-    key: Uuid,
+    key: String,
     content_type: String,
     raw: Vec<u8>,
 ) -> Result<(), BackendError> {
     use std::convert::TryFrom;
 
+    use futures::{future, stream};
+
     let len = i64::try_from(raw.len()).expect("raw data length must be within range of i64");
 
-    let request = PutObjectRequest {
-        acl: Some(store.acl.clone()),
-        body: Some(StreamingBody::from(raw)),
+    // shared between attempts rather than copied for each
+    let raw = Bytes::from(raw);
+
+    let make_request = || PutObjectRequest {
+        acl: Some(store.access.acl()),
+        body: Some(StreamingBody::new_with_size(
+            stream::once(future::ready(Ok::<_, io::Error>(raw.clone()))),
+            raw.len(),
+        )),
         bucket: store.bucket.clone(),
         cache_control: Some(store.cache_control.clone()),
         content_length: Some(len),
-        content_type: Some(content_type),
-        key: key.to_string(),
+        content_type: Some(content_type.clone()),
+        key: key.clone(),
         ..Default::default()
     };
 
-    let result = store.client.put_object(request).await;
+    let timeout = store.retry.transfer_timeout(raw.len() as u64);
+    let result = call(store, "put", timeout, || {
+        store.client.put_object(make_request())
+    })
+    .await?;
 
     match result {
         Ok(_) => Ok(()),
         Err(e) => Err(BackendError::UploadFailed { source: e }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use futures::future;
+    use rusoto_core::request::{BufferedHttpResponse, HttpClient};
+    use rusoto_core::{Region, RusotoError};
+    use rusoto_credential::{AwsCredentials, StaticProvider};
+    use rusoto_s3::S3Client;
+    use time::Date;
+    use url::Url;
+    use uuid::Uuid;
+    use warp::http::StatusCode;
+
+    use super::{call, parse_last_modified, Access, S3Store, Store};
+    use crate::errors::BackendError;
+    use crate::retry::{CircuitBreaker, RetryPolicy};
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    fn make_store(access: Access) -> S3Store {
+        make_store_with(access, RetryPolicy::default(), CircuitBreaker::default())
+    }
+
+    /// Makes a store that retries twice without waiting long and opens
+    /// its breaker after two calls fail.
+    fn make_flaky_store() -> S3Store {
+        let retry = RetryPolicy {
+            retries: 2,
+            delay: Duration::from_millis(1),
+            timeout: TIMEOUT,
+            min_transfer_rate: 0,
+        };
+
+        make_store_with(
+            Access::Private {
+                url_duration: Duration::from_secs(300),
+            },
+            retry,
+            CircuitBreaker::new(2, Duration::from_secs(60)),
+        )
+    }
+
+    fn make_store_with(access: Access, retry: RetryPolicy, breaker: CircuitBreaker) -> S3Store {
+        let region = Region::Custom {
+            name: "local".to_owned(),
+            endpoint: "http://localhost:9090".to_owned(),
+        };
+        let credentials = AwsCredentials::new("key", "secret", None, None);
+        let client = S3Client::new_with(
+            HttpClient::new().unwrap(),
+            StaticProvider::from(credentials.clone()),
+            region.clone(),
+        );
+
+        S3Store::new(
+            Arc::new(client),
+            "recordings".to_owned(),
+            "no-cache".to_owned(),
+            access,
+            region,
+            credentials,
+            retry,
+            breaker,
+        )
+    }
+
+    /// Calls the store, counting the attempts, each of which gets a
+    /// response with the given status.
+    async fn call_with_status(
+        store: &S3Store,
+        status: u16,
+    ) -> (u32, Result<Result<(), RusotoError<()>>, BackendError>) {
+        let attempts = AtomicU32::new(0);
+
+        let result = call(store, "test", TIMEOUT, || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+
+            future::ready(Err(RusotoError::Unknown(BufferedHttpResponse {
+                status: StatusCode::from_u16(status).unwrap(),
+                body: Default::default(),
+                headers: Default::default(),
+            })))
+        })
+        .await;
+
+        (attempts.into_inner(), result)
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors() {
+        let store = make_flaky_store();
+
+        let (attempts, result) = call_with_status(&store, 503).await;
+        assert_eq!(attempts, 3);
+        assert!(matches!(result, Ok(Err(RusotoError::Unknown(_)))));
+    }
+
+    #[tokio::test]
+    async fn retries_throttled_calls() {
+        let store = make_flaky_store();
+
+        let (attempts, result) = call_with_status(&store, 429).await;
+        assert_eq!(attempts, 3);
+        assert!(matches!(result, Ok(Err(RusotoError::Unknown(_)))));
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_client_errors() {
+        let store = make_flaky_store();
+
+        for _ in 0..3 {
+            let (attempts, result) = call_with_status(&store, 403).await;
+            assert_eq!(attempts, 1);
+            assert!(matches!(result, Ok(Err(RusotoError::Unknown(_)))));
+        }
+
+        // the request was at fault, not the store
+        assert!(!store.breaker.is_open());
+    }
+
+    #[tokio::test]
+    async fn retries_calls_that_time_out() {
+        let store = make_flaky_store();
+        let attempts = AtomicU32::new(0);
+
+        let result = call(&store, "test", TIMEOUT, || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            future::pending::<Result<(), RusotoError<()>>>()
+        })
+        .await;
+
+        assert_eq!(attempts.into_inner(), 3);
+        assert!(matches!(result, Ok(Err(RusotoError::HttpDispatch(_)))));
+    }
+
+    #[tokio::test]
+    async fn opens_the_breaker_after_repeated_failures() {
+        let store = make_flaky_store();
+
+        call_with_status(&store, 500).await;
+        assert!(!store.breaker.is_open());
+        call_with_status(&store, 500).await;
+        assert!(store.breaker.is_open());
+
+        let (attempts, result) = call_with_status(&store, 500).await;
+        assert_eq!(attempts, 0);
+        assert!(matches!(result, Err(BackendError::StoreUnavailable)));
+
+        assert!(matches!(
+            store.check().await,
+            Err(BackendError::StoreUnavailable)
+        ));
+    }
+
+    #[test]
+    fn public_stores_have_permanent_urls() {
+        let store = make_store(Access::Public {
+            acl: "public-read".to_owned(),
+            base_url: Url::parse("https://example.com/recordings/").unwrap(),
+        });
+        let key = format!("{}.ogg", Uuid::new_v4());
+
+        assert_eq!(
+            store.get_url(&key).unwrap().unwrap().as_str(),
+            format!("https://example.com/recordings/{}", key)
+        );
+        assert!(store.sign_url(&key).unwrap().is_none());
+    }
+
+    #[test]
+    fn private_stores_only_sign_urls() {
+        let store = make_store(Access::Private {
+            url_duration: Duration::from_secs(300),
+        });
+        let key = format!("recordings/2026/10/{}.ogg", Uuid::new_v4());
+
+        assert!(store.get_url(&key).unwrap().is_none());
+
+        let signed = store.sign_url(&key).unwrap().unwrap();
+        assert!(signed.url.path().ends_with(&key));
+        assert!(signed
+            .url
+            .query_pairs()
+            .any(|(name, value)| name == "X-Amz-Expires" && value == "300"));
+    }
+
+    #[test]
+    fn parses_listed_timestamps() {
+        let expected = Date::try_from_ymd(2026, 10, 18)
+            .unwrap()
+            .try_with_hms(22, 0, 5)
+            .unwrap()
+            .assume_utc();
+
+        assert_eq!(
+            parse_last_modified("2026-10-18T22:00:05.123Z"),
+            Some(expected)
+        );
+        assert_eq!(parse_last_modified("2026-10-18T22:00:05Z"), Some(expected));
+        assert_eq!(parse_last_modified("yesterday"), None);
+    }
+}
@@ -0,0 +1,46 @@
+//! Alternatives to offer for names that are already taken.
+
+use uuid::Uuid;
+
+use crate::normalization::normalize_name;
+
+/// How many suggestions to offer at most.
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// Returns candidate alternatives to a name, in order of preference,
+/// none longer than `max_length` characters. More are returned than
+/// are offered, as some may be taken too.
+pub fn candidates(name: &str, max_length: usize) -> Vec<String> {
+    let name = normalize_name(name);
+
+    // random suffixes make it likely that some candidates are free
+    // even when the small numbers are all used
+    let random = Uuid::new_v4().as_u128();
+    let suffixes = (2..=9)
+        .map(|n| n.to_string())
+        .chain((0..4).map(|i| format!("{}", 10 + (random >> (i * 16)) % 990)));
+
+    suffixes
+        .map(|suffix| format!("{} {}", name, suffix))
+        .filter(|candidate| candidate.chars().count() <= max_length)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::candidates;
+
+    #[test]
+    fn suggests_numbered_names() {
+        let candidates = candidates(" someone ", 100);
+
+        assert_eq!(candidates[0], "someone 2");
+        assert!(candidates.iter().all(|c| c.starts_with("someone ")));
+    }
+
+    #[test]
+    fn respects_the_length_limit() {
+        assert!(candidates("someone", 9).iter().all(|c| c.len() <= 9));
+        assert!(candidates("someone", 7).is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+//! Distributed tracing through OpenTelemetry.
+
+use std::fmt::Display;
+use std::future::Future;
+
+use opentelemetry::propagation::Extractor;
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::trace::{FutureExt, Span, StatusCode, TraceContextExt, TraceError, Tracer};
+use opentelemetry::{global, Context, KeyValue};
+use warp::http::{HeaderMap, Method};
+
+const TRACER_NAME: &str = "backend";
+
+/// Installs the W3C trace context propagator and, given the endpoint
+/// of an OTLP collector, an exporter. Without an endpoint, spans are
+/// discarded, as they are if the exporter fails to install.
+pub fn initialize(otlp_endpoint: Option<String>) -> Result<(), TraceError> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    match otlp_endpoint {
+        Some(endpoint) => install_exporter(endpoint),
+        None => Ok(()),
+    }
+}
+
+/// Exports any spans not yet sent.
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
+
+#[cfg(feature = "otlp")]
+fn install_exporter(endpoint: String) -> Result<(), TraceError> {
+    use opentelemetry::sdk::{trace, Resource};
+
+    opentelemetry_otlp::new_pipeline()
+        .with_endpoint(endpoint)
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                TRACER_NAME,
+            )])),
+        )
+        .install_batch(opentelemetry::runtime::Tokio)?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "otlp"))]
+fn install_exporter(_endpoint: String) -> Result<(), TraceError> {
+    Err(TraceError::from(
+        "must build with the `otlp` feature to export spans",
+    ))
+}
+
+/// The method of a request, carried in the trace context until its
+/// handler starts a span.
+struct Request {
+    method: String,
+}
+
+/// Extracts the trace started by the client from its `traceparent`
+/// header, if any. No span is started here, as the route isn't known
+/// until one matches.
+pub fn extract(method: &Method, headers: &HeaderMap) -> Context {
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract_with_context(&Context::new(), &HeaderExtractor(headers))
+    });
+
+    parent.with_value(Request {
+        method: method.to_string(),
+    })
+}
+
+/// Starts the span for the handler of a request to the named route.
+/// The span ends when the last copy of the returned context is
+/// dropped.
+pub fn start_handler(trace: &Context, route: &'static str) -> Context {
+    let method = match trace.get::<Request>() {
+        Some(request) => request.method.clone(),
+        None => String::new(),
+    };
+
+    let span = global::tracer(TRACER_NAME).start_with_context(route, trace.clone());
+    span.set_attribute(KeyValue::new("http.method", method));
+    span.set_attribute(KeyValue::new("http.route", route));
+
+    trace.with_span(span)
+}
+
+/// Runs one step of handling a request in a span of its own, marking
+/// the span as failed if the step fails.
+pub async fn in_span<T, E: Display>(
+    parent: &Context,
+    name: &'static str,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let span = global::tracer(TRACER_NAME).start_with_context(name, parent.clone());
+    let context = parent.with_span(span);
+
+    let result = future.with_context(context.clone()).await;
+
+    if let Err(e) = &result {
+        context.span().set_status(StatusCode::Error, e.to_string());
+    }
+
+    context.span().end();
+
+    result
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl<'a> Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::TraceContextExt;
+    use warp::http::{HeaderMap, Method};
+
+    use super::{extract, initialize, start_handler};
+
+    #[test]
+    fn continues_incoming_traces() {
+        initialize(None).expect("initialize tracing");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+                .parse()
+                .expect("parse traceparent header"),
+        );
+
+        let context = start_handler(&extract(&Method::GET, &headers), "test");
+
+        assert_eq!(
+            context.span().span_context().trace_id().to_hex(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+    }
+}
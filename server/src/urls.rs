@@ -41,4 +41,18 @@ impl Urls {
             .join(&id)
             .unwrap_or_else(|_| panic!("get URL for recording {}", id))
     }
+
+    pub fn upload(&self, id: &Uuid) -> Url {
+        let path = format!("uploads/{}", id);
+        self.recordings()
+            .join(&path)
+            .unwrap_or_else(|_| panic!("get URL for upload {}", id))
+    }
+
+    pub fn direct_upload(&self, id: &Uuid) -> Url {
+        let path = format!("direct-uploads/{}", id);
+        self.recordings()
+            .join(&path)
+            .unwrap_or_else(|_| panic!("get URL for direct upload {}", id))
+    }
 }
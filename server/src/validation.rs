@@ -0,0 +1,204 @@
+//! Checks on uploaded metadata beyond what deserializing it ensures.
+
+use serde::Serialize;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::blocklist::{self, BlockedTerm};
+use crate::recording::UploadMetadata;
+
+/// The longest values accepted for each free-text field, in
+/// characters after normalization.
+#[derive(Clone, Copy, Debug)]
+pub struct MetadataLimits {
+    pub name: usize,
+    pub location: usize,
+    pub occupation: usize,
+    pub email: usize,
+}
+
+impl Default for MetadataLimits {
+    fn default() -> Self {
+        Self {
+            name: 100,
+            location: 200,
+            occupation: 100,
+            email: 254,
+        }
+    }
+}
+
+/// Which labels referred to by the metadata exist and are enabled.
+#[derive(Clone, Copy, Debug)]
+pub struct LabelCheck {
+    pub age: bool,
+    pub gender: bool,
+    pub category: bool,
+}
+
+/// A problem with one field of the metadata.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: &'static str,
+
+    #[serde(flatten)]
+    pub problem: Problem,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "problem")]
+pub enum Problem {
+    Empty,
+    TooLong { max: usize },
+    ControlCharacters,
+    InvalidEmail,
+    UnknownLabel,
+    Blocked,
+}
+
+impl FieldError {
+    fn new(field: &'static str, problem: Problem) -> Self {
+        Self { field, problem }
+    }
+}
+
+/// Returns every problem with the metadata, or none if it’s valid.
+pub fn validate(
+    metadata: &UploadMetadata,
+    limits: &MetadataLimits,
+    labels: LabelCheck,
+    blocked_terms: &[BlockedTerm],
+) -> Vec<FieldError> {
+    let mut errors = validate_name(&metadata.name, limits, blocked_terms);
+
+    check_text(
+        &mut errors,
+        "location",
+        metadata.location.as_deref(),
+        limits.location,
+    );
+    check_text(
+        &mut errors,
+        "occupation",
+        metadata.occupation.as_deref(),
+        limits.occupation,
+    );
+    check_text(
+        &mut errors,
+        "email",
+        metadata.email.as_deref(),
+        limits.email,
+    );
+
+    for (field, value) in &[
+        ("location", metadata.location.as_deref()),
+        ("occupation", metadata.occupation.as_deref()),
+    ] {
+        if value.map_or(false, |v| blocklist::is_blocked(blocked_terms, v)) {
+            errors.push(FieldError::new(field, Problem::Blocked));
+        }
+    }
+
+    if let Some(email) = metadata.email.as_deref() {
+        if !is_valid_email(email) {
+            errors.push(FieldError::new("email", Problem::InvalidEmail));
+        }
+    }
+
+    if !labels.age {
+        errors.push(FieldError::new("age_id", Problem::UnknownLabel));
+    }
+
+    if !labels.gender {
+        errors.push(FieldError::new("gender_id", Problem::UnknownLabel));
+    }
+
+    if !labels.category {
+        errors.push(FieldError::new("category_id", Problem::UnknownLabel));
+    }
+
+    errors
+}
+
+/// Returns every problem with a name on its own, as checked both on
+/// upload and when reserving it.
+pub fn validate_name(
+    name: &str,
+    limits: &MetadataLimits,
+    blocked_terms: &[BlockedTerm],
+) -> Vec<FieldError> {
+    let mut errors = vec![];
+
+    if name.is_empty() {
+        errors.push(FieldError::new("name", Problem::Empty));
+    }
+
+    check_text(&mut errors, "name", Some(name), limits.name);
+
+    if blocklist::is_blocked(blocked_terms, name) {
+        errors.push(FieldError::new("name", Problem::Blocked));
+    }
+
+    errors
+}
+
+fn check_text(errors: &mut Vec<FieldError>, field: &'static str, value: Option<&str>, max: usize) {
+    let value = match value {
+        Some(value) => value,
+        None => return,
+    };
+
+    // count composed characters so that accents don’t count double
+    if value.nfc().count() > max {
+        errors.push(FieldError::new(field, Problem::TooLong { max }));
+    }
+
+    if value.chars().any(char::is_control) {
+        errors.push(FieldError::new(field, Problem::ControlCharacters));
+    }
+}
+
+/// Checks the rough shape of an email address. Whether it can receive
+/// mail is only known by sending some.
+fn is_valid_email(email: &str) -> bool {
+    let (local, domain) = match email.rsplit_once('@') {
+        Some(parts) => parts,
+        None => return false,
+    };
+
+    let local_is_valid = !local.is_empty()
+        && local.len() <= 64
+        && !local.contains('@')
+        && !local.chars().any(char::is_whitespace);
+
+    let labels = domain.split('.').collect::<Vec<_>>();
+    let domain_is_valid = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        });
+
+    local_is_valid && domain_is_valid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_valid_email;
+
+    #[test]
+    fn accepts_ordinary_emails() {
+        assert!(is_valid_email("someone@example.com"));
+        assert!(is_valid_email("some.one+tag@mail.example.co.uk"));
+    }
+
+    #[test]
+    fn rejects_malformed_emails() {
+        assert!(!is_valid_email("someone"));
+        assert!(!is_valid_email("@example.com"));
+        assert!(!is_valid_email("someone@localhost"));
+        assert!(!is_valid_email("some one@example.com"));
+        assert!(!is_valid_email("someone@example..com"));
+        assert!(!is_valid_email("a@b@example.com"));
+    }
+}
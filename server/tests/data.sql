@@ -17,6 +17,7 @@ TRUNCATE categories CASCADE;
 TRUNCATE genders CASCADE;
 TRUNCATE mime_types CASCADE;
 TRUNCATE recording_tokens CASCADE;
+TRUNCATE blocked_terms;
 
 INSERT INTO ages (id, label, enabled) VALUES (1, 'Age 1', TRUE);
 INSERT INTO ages (id, label, enabled) VALUES (2, 'Age B', TRUE);
@@ -24,15 +25,15 @@ INSERT INTO ages (id, label, enabled) VALUES (3, 'Age three', TRUE);
 INSERT INTO ages (id, label, enabled) VALUES (4, 'Fooled ya! This is Age 2', TRUE);
 INSERT INTO ages (id, label, enabled) VALUES (20, 'This age doesn''t exist', FALSE);
 
-INSERT INTO categories (id, label, enabled) VALUES (6, 'This is a category', TRUE);
-INSERT INTO categories (id, label, enabled) VALUES (2, 'Some other category', TRUE);
-INSERT INTO categories (id, label, enabled) VALUES (5, 'This one is disabled', FALSE);
-INSERT INTO categories (id, label, enabled) VALUES (7, 'This category has
+INSERT INTO categories (id, label, enabled, sort_order) VALUES (6, 'This is a category', TRUE, 1);
+INSERT INTO categories (id, label, enabled, sort_order) VALUES (2, 'Some other category', TRUE, 2);
+INSERT INTO categories (id, label, enabled, sort_order) VALUES (5, 'This one is disabled', FALSE, 3);
+INSERT INTO categories (id, label, enabled, sort_order) VALUES (7, 'This category has
   some newlines
-and spaces in it', TRUE);
-INSERT INTO categories (id, label, enabled, description) VALUES (3, 'यह हिन्दी है ।', TRUE, 'This is a description');
-INSERT INTO categories (id, label, enabled) VALUES (4, 'Ceci n’est pas une catégorie', TRUE);
-INSERT INTO categories (id, label, enabled) VALUES (1, 'یہ بھی ہے', TRUE);
+and spaces in it', TRUE, 4);
+INSERT INTO categories (id, label, enabled, description, sort_order) VALUES (3, 'यह हिन्दी है ।', TRUE, 'This is a description', 5);
+INSERT INTO categories (id, label, enabled, sort_order) VALUES (4, 'Ceci n’est pas une catégorie', TRUE, 6);
+INSERT INTO categories (id, label, enabled, sort_order) VALUES (1, 'یہ بھی ہے', TRUE, 7);
 
 INSERT INTO genders (id, label, enabled) VALUES (1, 'One of the genders', TRUE);
 INSERT INTO genders (id, label, enabled) VALUES (2, 'Some other genders', TRUE);
@@ -54,7 +55,7 @@ INSERT INTO recording_tokens (id, parent_id) VALUES ('e900020a-3ed4-11eb-84c7-02
 -- Name: ages_id_seq; Type: SEQUENCE SET; Schema: public; Owner: postgres
 --
 
-SELECT pg_catalog.setval('ages_id_seq', 4, TRUE);
+SELECT pg_catalog.setval('ages_id_seq', 20, TRUE);
 
 
 --
@@ -63,7 +64,7 @@ SELECT pg_catalog.setval('ages_id_seq', 4, TRUE);
 -- Name: categories_id_seq; Type: SEQUENCE SET; Schema: public; Owner: postgres
 --
 
-SELECT pg_catalog.setval('categories_id_seq', 6, TRUE);
+SELECT pg_catalog.setval('categories_id_seq', 7, TRUE);
 
 
 --
@@ -72,7 +73,14 @@ SELECT pg_catalog.setval('categories_id_seq', 6, TRUE);
 -- Name: genders_id_seq; Type: SEQUENCE SET; Schema: public; Owner: postgres
 --
 
-SELECT pg_catalog.setval('genders_id_seq', 4, TRUE);
+SELECT pg_catalog.setval('genders_id_seq', 50, TRUE);
+
+
+--
+-- Name: mime_types_id_seq; Type: SEQUENCE SET; Schema: public; Owner: postgres
+--
+
+SELECT pg_catalog.setval('mime_types_id_seq', 2, TRUE);
 
 
 --
@@ -15,6 +15,9 @@ use backend::config::get_variable;
 #[serde(deny_unknown_fields)]
 struct CreationResponse {
     message: Option<String>,
+    code: Option<String>,
+    details: Option<serde_json::Value>,
+    request_id: Option<String>,
     id: Option<String>,
     tokens: Option<Vec<String>>,
     key: Option<String>,
@@ -65,6 +68,78 @@ struct RandomRecording {
     location: Option<String>,
 }
 
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct RecentResponse {
+    recordings: Vec<RecentRecording>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct RecentRecording {
+    id: String,
+    name: Option<String>,
+    parent: Option<String>,
+    created_at: i64,
+    deleted: bool,
+    hidden: bool,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct ReportResponse {
+    id: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct ReportsResponse {
+    reports: Vec<Report>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct Report {
+    id: String,
+    recording_id: String,
+    reason: String,
+    note: Option<String>,
+    created_at: i64,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct LabelResponse {
+    id: i16,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct ManagedLabel {
+    id: i16,
+    label: String,
+    description: Option<String>,
+    enabled: bool,
+    sort_order: i16,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct FormatResponse {
+    id: i16,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct ManagedFormat {
+    id: i16,
+    container: String,
+    codec: String,
+    extension: String,
+    essence: String,
+    enabled: bool,
+}
+
 #[derive(Debug, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields)]
 struct RelatedLabel(i16, String, Option<String>);
@@ -74,6 +149,8 @@ type ChildOutput = Arc<RwLock<Vec<String>>>;
 const BOUNDARY: &str = "thisisaboundary1234";
 const TOKENS_PER_RECORDING: u8 = 4;
 const RECORDINGS_PATH: &str = "recs";
+const REPORTS_PER_HOUR: u16 = 3;
+const ADMIN_TOKEN: &str = "thisisanadmintoken";
 
 #[tokio::test]
 async fn api_works() {
@@ -104,7 +181,7 @@ async fn api_works() {
     }
     .await;
 
-    let _ = reqwest::Client::new()
+    let _ = admin_client()
         .post(format!("{}/terminate", admin_url))
         .send()
         .await
@@ -122,12 +199,15 @@ async fn api_works() {
 }
 
 async fn test_api() {
+    test_admin_authentication().await;
+    test_readiness().await;
     test_formats().await;
     test_ages().await;
     test_categories().await;
     test_genders().await;
 
     test_non_existent_recording().await;
+    test_request_ids().await;
     test_bad_uploads().await;
 
     let content_type = multipart_content_type(&BOUNDARY);
@@ -137,10 +217,13 @@ async fn test_api() {
     let file_path = base_path.join("tests").join("opus_file.ogg");
     let failing_file_path = base_path.join("tests").join("failing.webm");
 
+    test_invalid_metadata(&file_path, &content_type).await;
+
     let (id, tokens, key) = test_upload(&file_path, &failing_file_path, &content_type).await;
     test_duplicate_upload(&file_path, &content_type).await;
 
     test_key(&id, key).await;
+    test_reservations(&file_path, &content_type, &tokens).await;
 
     let children: serde_json::Value = serde_json::from_reader(
         fs::File::open("tests/simple_metadata_children.json")
@@ -162,13 +245,27 @@ async fn test_api() {
             .collect::<Vec<_>>(),
     )
     .await;
+    test_status_codes(id_to_delete).await;
+    test_audio(&file_path, &id).await;
+
+    test_count(5).await;
 
-    test_count().await;
+    test_random(5).await;
 
-    test_random().await;
+    let (first_id, tokens, _) = results[0].to_owned();
+    test_token(tokens[0].to_owned(), first_id).await;
 
-    let (id, tokens, _) = results[0].to_owned();
-    test_token(tokens[0].to_owned(), id).await;
+    test_moderation(&results[1].0, &id, &results[3].0).await;
+
+    test_reports(&results[0].0).await;
+
+    test_labels().await;
+    test_translations().await;
+    test_format_management().await;
+    test_blocklist().await;
+    test_resumable_upload(&file_path, &tokens[1]).await;
+    test_direct_upload(&file_path, &tokens[2]).await;
+    test_metrics().await;
 }
 
 async fn start_server() -> (Child, Vec<String>) {
@@ -185,6 +282,10 @@ async fn start_server() -> (Child, Vec<String>) {
             TOKENS_PER_RECORDING.to_string(),
         ),
         ("BACKEND_RECORDINGS_PATH", RECORDINGS_PATH.to_string()),
+        ("BACKEND_REPORTS_PER_HOUR", REPORTS_PER_HOUR.to_string()),
+        ("BACKEND_ADMIN_TOKEN", ADMIN_TOKEN.to_string()),
+        ("BACKEND_REPORTS_TO_HIDE", "2".to_string()),
+        ("BACKEND_TRUST_FORWARDED_FOR", "1".to_string()),
     ];
 
     #[allow(unused_variables)]
@@ -474,7 +575,42 @@ async fn test_upload(
             .await
             .expect("get /availability");
 
-        assert_eq!(response.status(), 403);
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        #[derive(Debug, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct SuggestionsResponse {
+            suggestions: Vec<String>,
+        }
+
+        let SuggestionsResponse { suggestions } = response
+            .json()
+            .await
+            .expect("deserialize suggestions response");
+        assert!(
+            !suggestions.is_empty(),
+            "taken names must come with suggestions"
+        );
+        assert!(suggestions.len() <= 5);
+
+        for suggestion in suggestions {
+            assert!(suggestion.starts_with("an UPLOADER "), "{}", suggestion);
+
+            let mut url = url_to(Some("available".to_owned()));
+            url.query_pairs_mut().append_pair("name", &suggestion);
+
+            let response = reqwest::get(url).await.expect("get /available");
+            assert_eq!(response.status(), StatusCode::OK, "{}", suggestion);
+        }
+    }
+
+    // names differing only in case or by lookalike characters are taken too
+    for lookalike in &["AN uploader", "\u{430}n UPL\u{41e}ADER"] {
+        let mut url = url_to(Some("available".to_owned()));
+        url.query_pairs_mut().append_pair("name", lookalike);
+
+        let response = reqwest::get(url).await.expect("get /available");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{}", lookalike);
     }
 
     (id, tokens, key)
@@ -507,6 +643,11 @@ async fn test_duplicate_upload(file_path: impl AsRef<Path>, content_type: impl A
             deserialized.message.unwrap().starts_with("invalid token"),
             "error response must mention invalid token"
         );
+        assert_eq!(deserialized.code.as_deref(), Some("invalid_token"));
+        assert!(
+            deserialized.details.unwrap()["token"].is_string(),
+            "error response must include the token"
+        );
     }
 
     // ensure the name cannot be reused
@@ -536,6 +677,7 @@ async fn test_duplicate_upload(file_path: impl AsRef<Path>, content_type: impl A
             Some("name already exists in database".to_owned()),
             "error response must mention name already exists in database"
         );
+        assert_eq!(deserialized.code.as_deref(), Some("name_taken"));
     }
 }
 
@@ -554,6 +696,295 @@ async fn test_key(id: &str, token: String) {
     assert_eq!(recording.id, id);
 }
 
+async fn test_reservations(
+    file_path: impl AsRef<Path>,
+    content_type: impl AsRef<str>,
+    tokens: &[String],
+) {
+    use uuid::Uuid;
+
+    let reserve = |name: &str, token: &str| {
+        let body = serde_json::json!({ "name": name, "token": token });
+
+        async move {
+            reqwest::Client::new()
+                .post(url_to(Some("reserve".to_owned())))
+                .json(&body)
+                .send()
+                .await
+                .expect("post /reserve")
+        }
+    };
+
+    let availability = |name: &str| {
+        let mut url = url_to(Some("available".to_owned()));
+        url.query_pairs_mut().append_pair("name", name);
+
+        async move { reqwest::get(url).await.expect("get /available").status() }
+    };
+
+    let response = reserve(" Reserved name ", &tokens[0]).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let body: serde_json::Value = response.json().await.expect("parse reservation");
+    assert_eq!(body["name"], "Reserved name");
+    assert!(body["expires_at"].as_i64().is_some());
+
+    assert_eq!(availability("reserved NAME").await, StatusCode::FORBIDDEN);
+
+    // the same token can renew its reservation, but no other can take it
+    assert_eq!(
+        reserve("Reserved name", &tokens[0]).await.status(),
+        StatusCode::CREATED
+    );
+
+    let response = reserve("reserved name", &tokens[1]).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = response.json().await.expect("parse error response");
+    assert_eq!(body["code"], "name_taken");
+
+    let response = reserve("Unreserved name", &Uuid::new_v4().to_string()).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = reserve("", &tokens[1]).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // names already taken by a recording can't be reserved either
+    assert_eq!(
+        reserve("An uploader", &tokens[1]).await.status(),
+        StatusCode::FORBIDDEN
+    );
+
+    {
+        let metadata = serde_json::json!({
+            "name": "Reserved name",
+            "category_id": 2,
+            "token": tokens[1],
+        });
+
+        let response = upload_file(
+            file_path.as_ref(),
+            content_type.as_ref(),
+            BOUNDARY.as_bytes(),
+            metadata.to_string().as_bytes(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    // reserving another name frees the first, and the name is left
+    // reserved for the child uploaded with this token later
+    assert_eq!(
+        reserve("Myself", &tokens[0]).await.status(),
+        StatusCode::CREATED
+    );
+    assert_eq!(availability("Reserved name").await, StatusCode::OK);
+    assert_eq!(availability("Myself").await, StatusCode::FORBIDDEN);
+}
+
+async fn test_resumable_upload(file_path: impl AsRef<Path>, token: &str) {
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct ProgressResponse {
+        id: String,
+        offset: u64,
+        length: u64,
+    }
+
+    let audio = fs::read(file_path).expect("read audio file");
+    let metadata = serde_json::json!({
+        "name": "Someone on a train",
+        "category_id": 2,
+        "token": token,
+    });
+    let client = reqwest::Client::new();
+
+    let start = |length: usize| {
+        client
+            .post(url_to(Some("uploads".to_owned())))
+            .header("content-type", "application/json")
+            .header("upload-length", length.to_string())
+            .body(metadata.to_string())
+            .send()
+    };
+
+    let response = start(0).await.expect("post /uploads");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = start(audio.len()).await.expect("post /uploads");
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(response.headers()["upload-offset"], "0");
+    assert!(response.headers().contains_key("location"));
+
+    let progress: ProgressResponse = response.json().await.expect("parse upload progress");
+    assert_eq!(progress.offset, 0);
+    assert_eq!(progress.length, audio.len() as u64);
+
+    // the token stays locked while the upload is in progress
+    let response = start(audio.len()).await.expect("post /uploads");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let upload_url = url_to(Some(format!("uploads/{}", progress.id)));
+    let send_chunk = |offset: usize, chunk: Vec<u8>| {
+        client
+            .patch(upload_url.clone())
+            .header("content-type", "application/offset+octet-stream")
+            .header("upload-offset", offset.to_string())
+            .body(chunk)
+            .send()
+    };
+
+    let (first, rest) = audio.split_at(audio.len() / 2);
+
+    let response = send_chunk(0, first.to_vec()).await.expect("patch upload");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        response.headers()["upload-offset"],
+        first.len().to_string().as_str()
+    );
+
+    // resending a chunk that already arrived is refused with the
+    // offset to carry on from
+    let response = send_chunk(0, first.to_vec()).await.expect("patch upload");
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body: serde_json::Value = response.json().await.expect("parse error response");
+    assert_eq!(body["code"], "upload_offset_mismatch");
+    assert_eq!(body["details"]["expected"], first.len());
+
+    let response = client
+        .get(upload_url.clone())
+        .send()
+        .await
+        .expect("get upload");
+    assert_eq!(response.status(), StatusCode::OK);
+    let resumed: ProgressResponse = response.json().await.expect("parse upload progress");
+    assert_eq!(resumed.offset, first.len() as u64);
+
+    let mut too_long = rest.to_vec();
+    too_long.push(0);
+    let response = send_chunk(first.len(), too_long)
+        .await
+        .expect("patch upload");
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let response = send_chunk(first.len(), rest.to_vec())
+        .await
+        .expect("patch upload");
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let created: CreationResponse = response.json().await.expect("parse creation response");
+    let id = created.id.expect("get ID of resumed upload");
+    assert_eq!(
+        created.tokens.map(|t| t.len()),
+        Some(TOKENS_PER_RECORDING as usize)
+    );
+
+    let response = client.get(upload_url).send().await.expect("get upload");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = reqwest::get(url_to(Some(format!("id/{}", id))))
+        .await
+        .expect("get resumed recording");
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn test_direct_upload(file_path: impl AsRef<Path>, token: &str) {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct TargetResponse {
+        id: String,
+        method: String,
+        url: String,
+        headers: HashMap<String, String>,
+        expires_at: i64,
+    }
+
+    let audio = fs::read(file_path).expect("read audio file");
+    let metadata = serde_json::json!({
+        "name": "Someone on a bus",
+        "category_id": 2,
+        "token": token,
+    });
+    let client = reqwest::Client::new();
+
+    let start = |content_type: &str| {
+        client
+            .post(url_to(Some("direct-uploads".to_owned())))
+            .header("content-type", "application/json")
+            .header("upload-length", audio.len().to_string())
+            .header("upload-content-type", content_type)
+            .body(metadata.to_string())
+            .send()
+    };
+
+    let response = start("video/mp4").await.expect("post /direct-uploads");
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let body: serde_json::Value = response.json().await.expect("parse error response");
+    assert_eq!(body["code"], "unsupported_content_type");
+
+    let response = start("audio/ogg; codec=opus")
+        .await
+        .expect("post /direct-uploads");
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert!(response.headers().contains_key("location"));
+
+    let target: TargetResponse = response.json().await.expect("parse upload target");
+    assert_eq!(target.method, "PUT");
+    assert!(target.expires_at > 0);
+
+    // the token stays locked until the upload is finalized
+    let response = start("audio/ogg; codec=opus")
+        .await
+        .expect("post /direct-uploads");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let finalize_url = url_to(Some(format!("direct-uploads/{}/finalize", target.id)));
+
+    let response = client
+        .post(finalize_url.clone())
+        .send()
+        .await
+        .expect("finalize upload");
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body: serde_json::Value = response.json().await.expect("parse error response");
+    assert_eq!(body["code"], "upload_incomplete");
+
+    let mut request = client.put(&target.url).body(audio.clone());
+    for (name, value) in &target.headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    let response = request.send().await.expect("put to store");
+    assert!(response.status().is_success());
+
+    let response = client
+        .post(finalize_url.clone())
+        .send()
+        .await
+        .expect("finalize upload");
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let created: CreationResponse = response.json().await.expect("parse creation response");
+    let id = created.id.expect("get ID of direct upload");
+    assert_eq!(
+        created.tokens.map(|t| t.len()),
+        Some(TOKENS_PER_RECORDING as usize)
+    );
+
+    let response = client
+        .post(finalize_url)
+        .send()
+        .await
+        .expect("finalize upload");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = reqwest::get(url_to(Some(format!("id/{}", id))))
+        .await
+        .expect("get direct recording");
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
 async fn test_uploading_children(
     file_path: impl AsRef<Path>,
     content_type: impl AsRef<str>,
@@ -725,7 +1156,138 @@ async fn test_deletion(
     }
 }
 
-async fn test_count() {
+async fn test_status_codes(deleted_id: &str) {
+    use uuid::Uuid;
+
+    async fn check(method: reqwest::Method, path: String, status: StatusCode, code: &str) {
+        let response = reqwest::Client::new()
+            .request(method.clone(), url_to(Some(path.clone())))
+            .send()
+            .await
+            .expect(&format!("{} {}", method, path));
+        assert_eq!(response.status(), status, "{} {}", method, path);
+
+        let body: serde_json::Value = response.json().await.expect("parse error response");
+        assert_eq!(body["code"], code, "{} {}", method, path);
+    }
+
+    let missing_id = Uuid::new_v4();
+
+    check(
+        reqwest::Method::DELETE,
+        format!("id/{}/", missing_id),
+        StatusCode::NOT_FOUND,
+        "recording_not_found",
+    )
+    .await;
+    check(
+        reqwest::Method::GET,
+        format!("id/{}/children", missing_id),
+        StatusCode::NOT_FOUND,
+        "recording_not_found",
+    )
+    .await;
+    check(
+        reqwest::Method::GET,
+        format!("id/{}/audio", missing_id),
+        StatusCode::NOT_FOUND,
+        "recording_not_found",
+    )
+    .await;
+    check(
+        reqwest::Method::GET,
+        format!("token/{}/", missing_id),
+        StatusCode::NOT_FOUND,
+        "token_not_found",
+    )
+    .await;
+    check(
+        reqwest::Method::GET,
+        format!("lookup/{}/", missing_id),
+        StatusCode::NOT_FOUND,
+        "key_not_found",
+    )
+    .await;
+
+    check(
+        reqwest::Method::DELETE,
+        format!("id/{}/", deleted_id),
+        StatusCode::GONE,
+        "recording_deleted",
+    )
+    .await;
+    check(
+        reqwest::Method::GET,
+        format!("id/{}/children", deleted_id),
+        StatusCode::GONE,
+        "recording_deleted",
+    )
+    .await;
+    check(
+        reqwest::Method::GET,
+        format!("id/{}/audio", deleted_id),
+        StatusCode::GONE,
+        "recording_deleted",
+    )
+    .await;
+
+    check(
+        reqwest::Method::DELETE,
+        "id/not-an-id/".to_string(),
+        StatusCode::BAD_REQUEST,
+        "invalid_id",
+    )
+    .await;
+    check(
+        reqwest::Method::GET,
+        "id/not-an-id/children".to_string(),
+        StatusCode::BAD_REQUEST,
+        "invalid_id",
+    )
+    .await;
+}
+
+async fn test_audio(file_path: impl AsRef<Path>, id: &str) {
+    let audio = fs::read(file_path).expect("read audio file");
+    let audio_url = url_to(Some(format!("id/{}/audio", id)));
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(audio_url.clone())
+        .send()
+        .await
+        .expect("get audio");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["accept-ranges"], "bytes");
+    assert_eq!(response.headers()["content-type"], "audio/ogg; codec=opus");
+    let etag = response.headers()["etag"].to_owned();
+    let body = response.bytes().await.expect("read audio");
+    assert_eq!(body.as_ref(), audio.as_slice());
+
+    let response = client
+        .get(audio_url.clone())
+        .header("range", "bytes=0-9")
+        .send()
+        .await
+        .expect("get audio range");
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        response.headers()["content-range"],
+        format!("bytes 0-9/{}", audio.len()).as_str()
+    );
+    let body = response.bytes().await.expect("read audio range");
+    assert_eq!(body.as_ref(), &audio[..10]);
+
+    let response = client
+        .get(audio_url)
+        .header("if-none-match", etag)
+        .send()
+        .await
+        .expect("get unchanged audio");
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+}
+
+async fn test_count(expected: i64) {
     let response = reqwest::get(url_to(Some("count".to_string())))
         .await
         .expect("get /count");
@@ -736,10 +1298,10 @@ async fn test_count() {
         .parse::<i64>()
         .expect("parse count response as i64");
 
-    assert_eq!(count, 5);
+    assert_eq!(count, expected);
 }
 
-async fn test_random() {
+async fn test_random(expected: usize) {
     use std::collections::HashSet;
 
     let response = reqwest::get(url_to(Some("random/10".to_string())))
@@ -757,7 +1319,7 @@ async fn test_random() {
         .map(|r| r.id)
         .collect::<HashSet<_>>();
 
-    assert_eq!(recordings.len(), 5);
+    assert_eq!(recordings.len(), expected);
 }
 
 async fn test_token(token_id: String, parent_id: String) {
@@ -787,34 +1349,809 @@ async fn test_token(token_id: String, parent_id: String) {
     }
 }
 
-async fn test_bad_uploads() {
+async fn test_moderation(id_to_hide: &str, parent: &str, id_to_purge: &str) {
+    let client = admin_client();
+
     {
-        let response = reqwest::Client::new()
-            .request(reqwest::Method::POST, url_to(None))
-            .header("content-type", "text/plain")
-            .header("content-length", 0)
+        let response = admin_client()
+            .get(admin_url_to("recent/10"))
             .send()
             .await
-            .expect("make request");
+            .expect("get /recent/10");
+        assert_eq!(response.status(), StatusCode::OK);
 
-        // should fail because of `content-type`
-        let status = response.status();
-        assert!(status.is_client_error());
-        assert_eq!(status.as_u16(), 400);
+        let parsed: RecentResponse =
+            serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+                .expect("deserialize recent recordings");
+        let recording = parsed
+            .recordings
+            .iter()
+            .find(|r| r.id == id_to_hide)
+            .expect("find recording to hide in recent recordings");
+        assert_eq!(recording.parent.as_deref(), Some(parent));
+        assert!(!recording.hidden);
+        assert!(!recording.deleted);
     }
-}
-
-async fn test_non_existent_recording() {
-    use uuid::Uuid;
 
-    let path = format!("id/{id}", id = Uuid::new_v4());
-    let response = reqwest::get(url_to(Some(path.clone())))
+    let response = client
+        .post(admin_url_to(&format!("id/{}/hide", id_to_hide)))
+        .send()
+        .await
+        .expect("hide recording");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    test_count(4).await;
+    test_random(4).await;
+
+    {
+        let path = format!("id/{id}/children", id = parent);
+        let response = reqwest::get(url_to(Some(path.clone())))
+            .await
+            .expect(&format!("get /{}", path));
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let returned_ids =
+            parse_children_ids(&response.bytes().await.expect("get response body as bytes"));
+        assert!(!returned_ids.iter().any(|i| i == id_to_hide));
+    }
+
+    let response = client
+        .post(admin_url_to(&format!("id/{}/restore", id_to_hide)))
+        .send()
+        .await
+        .expect("restore recording");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    test_count(5).await;
+
+    {
+        use uuid::Uuid;
+
+        let response = client
+            .post(admin_url_to(&format!("id/{}/hide", Uuid::new_v4())))
+            .send()
+            .await
+            .expect("hide non-existent recording");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    let response = client
+        .delete(admin_url_to(&format!("id/{}", parent)))
+        .send()
+        .await
+        .expect("purge recording with children");
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = client
+        .delete(admin_url_to(&format!("id/{}", id_to_purge)))
+        .send()
+        .await
+        .expect("purge recording");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let path = format!("id/{id}", id = id_to_purge);
+    let response = reqwest::get(url_to(Some(path.clone())))
+        .await
+        .expect(&format!("get {}", path));
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    test_count(4).await;
+}
+
+async fn test_reports(id: &str) {
+    let client = admin_client();
+    let report_url = url_to(Some(format!("id/{}/report", id)));
+
+    {
+        let response = client
+            .post(report_url.clone())
+            .header("content-type", "application/json")
+            .body(serde_json::json!({ "reason": "not a reason" }).to_string())
+            .send()
+            .await
+            .expect("submit report with invalid reason");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    {
+        use uuid::Uuid;
+
+        let response = client
+            .post(url_to(Some(format!("id/{}/report", Uuid::new_v4()))))
+            .header("content-type", "application/json")
+            .body(serde_json::json!({ "reason": "spam" }).to_string())
+            .send()
+            .await
+            .expect("report non-existent recording");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    let response = client
+        .post(report_url.clone())
+        .header("content-type", "application/json")
+        .body(serde_json::json!({ "reason": "offensive", "note": " rude \n" }).to_string())
+        .send()
+        .await
+        .expect("submit report");
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let report: ReportResponse =
+        serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+            .expect("deserialize report response");
+
+    // a second report from the same address doesn't hide the recording
+    let response = client
+        .post(report_url.clone())
+        .header("content-type", "application/json")
+        .header("x-forwarded-for", "127.0.0.1")
+        .body(serde_json::json!({ "reason": "spam" }).to_string())
+        .send()
+        .await
+        .expect("submit report from the same address");
+    assert_eq!(response.status(), StatusCode::CREATED);
+    test_count(4).await;
+
+    // but one from another address does
+    let response = client
+        .post(report_url.clone())
+        .header("content-type", "application/json")
+        .header("x-forwarded-for", "203.0.113.7, 127.0.0.1")
+        .body(serde_json::json!({ "reason": "spam" }).to_string())
+        .send()
+        .await
+        .expect("submit report from another address");
+    assert_eq!(response.status(), StatusCode::CREATED);
+    test_count(3).await;
+
+    let response = admin_client()
+        .get(admin_url_to("reports"))
+        .send()
+        .await
+        .expect("get /reports");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let parsed: ReportsResponse =
+        serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+            .expect("deserialize reports");
+    let open = parsed
+        .reports
+        .iter()
+        .find(|r| r.id == report.id)
+        .expect("find report among open reports");
+    assert_eq!(open.recording_id, id);
+    assert_eq!(open.reason, "offensive");
+    assert_eq!(open.note.as_deref(), Some("rude"));
+
+    let resolve_url = admin_url_to(&format!("reports/{}/resolve", report.id));
+
+    let response = client
+        .post(resolve_url.clone())
+        .send()
+        .await
+        .expect("resolve report");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = client
+        .post(resolve_url)
+        .send()
+        .await
+        .expect("resolve report again");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = client
+        .post(admin_url_to(&format!("id/{}/restore", id)))
+        .send()
+        .await
+        .expect("restore reported recording");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    // two of the reports so far came from this address
+    for _ in 2..REPORTS_PER_HOUR {
+        let response = client
+            .post(report_url.clone())
+            .header("content-type", "application/json")
+            .body(serde_json::json!({ "reason": "spam" }).to_string())
+            .send()
+            .await
+            .expect("submit report");
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    let response = client
+        .post(report_url)
+        .header("content-type", "application/json")
+        .body(serde_json::json!({ "reason": "spam" }).to_string())
+        .send()
+        .await
+        .expect("submit one report too many");
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+async fn test_labels() {
+    async fn get_ages() -> Vec<RelatedLabel> {
+        let response = reqwest::get(url_to(Some("ages".to_string())))
+            .await
+            .expect("get /ages");
+        assert_eq!(response.status(), 200);
+
+        serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+            .expect("parse response as Vec<RelatedLabel>")
+    }
+
+    let client = admin_client();
+    let original_ages = get_ages().await;
+
+    let new_age = serde_json::json!({ "label": "Age five", "description": "A new age" });
+
+    let response = client
+        .post(admin_url_to("labels/ages"))
+        .header("content-type", "application/json")
+        .body(new_age.to_string())
+        .send()
+        .await
+        .expect("create age");
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let LabelResponse { id } =
+        serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+            .expect("deserialize label response");
+
+    let ages = get_ages().await;
+    assert_eq!(
+        ages.last(),
+        Some(&RelatedLabel(
+            id,
+            "Age five".to_owned(),
+            Some("A new age".to_owned())
+        ))
+    );
+
+    let response = client
+        .post(admin_url_to("labels/ages"))
+        .header("content-type", "application/json")
+        .body(new_age.to_string())
+        .send()
+        .await
+        .expect("create duplicate age");
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let mut order = vec![id];
+    order.extend(original_ages.iter().map(|RelatedLabel(id, _, _)| *id));
+
+    let response = client
+        .put(admin_url_to("labels/ages/order"))
+        .header("content-type", "application/json")
+        .body(serde_json::json!(order).to_string())
+        .send()
+        .await
+        .expect("reorder ages");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(get_ages().await.first().map(|l| l.0), Some(id));
+
+    let response = client
+        .put(admin_url_to("labels/ages/order"))
+        .header("content-type", "application/json")
+        .body(serde_json::json!([id, 999]).to_string())
+        .send()
+        .await
+        .expect("reorder ages with non-existent age");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = client
+        .put(admin_url_to(&format!("labels/ages/{}", id)))
+        .header("content-type", "application/json")
+        .body(serde_json::json!({ "label": "Age five", "enabled": false }).to_string())
+        .send()
+        .await
+        .expect("disable age");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(get_ages().await, original_ages);
+
+    let response = admin_client()
+        .get(admin_url_to("labels/ages"))
+        .send()
+        .await
+        .expect("get all ages");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let all_ages: Vec<ManagedLabel> =
+        serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+            .expect("deserialize managed labels");
+    assert_eq!(
+        all_ages.iter().find(|l| l.id == id),
+        Some(&ManagedLabel {
+            id,
+            label: "Age five".to_owned(),
+            description: None,
+            enabled: false,
+            sort_order: 1,
+        })
+    );
+    assert!(all_ages.iter().any(|l| l.id == 20 && !l.enabled));
+}
+
+async fn test_translations() {
+    async fn get_category(query: &str, accept_language: Option<&str>) -> RelatedLabel {
+        let mut request = reqwest::Client::new().get(url_to(Some(format!("categories{}", query))));
+
+        if let Some(accept_language) = accept_language {
+            request = request.header("accept-language", accept_language);
+        }
+
+        let response = request.send().await.expect("get /categories");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("vary").map(|v| v.as_bytes()),
+            Some(&b"accept-language"[..])
+        );
+
+        let categories: Vec<RelatedLabel> =
+            serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+                .expect("parse response as Vec<RelatedLabel>");
+
+        categories
+            .into_iter()
+            .find(|RelatedLabel(id, _, _)| *id == 2)
+            .expect("find category 2")
+    }
+
+    let client = admin_client();
+    let original = RelatedLabel(2, "Some other category".to_owned(), None);
+    let translated = RelatedLabel(
+        2,
+        "Une autre catégorie".to_owned(),
+        Some("Une description".to_owned()),
+    );
+
+    let response = client
+        .put(admin_url_to("labels/categories/2/translations/FR"))
+        .header("content-type", "application/json")
+        .body(
+            serde_json::json!({ "label": "Une autre catégorie", "description": "Une description" })
+                .to_string(),
+        )
+        .send()
+        .await
+        .expect("save translation");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = client
+        .put(admin_url_to("labels/categories/999/translations/fr"))
+        .header("content-type", "application/json")
+        .body(serde_json::json!({ "label": "Rien" }).to_string())
+        .send()
+        .await
+        .expect("save translation for non-existent category");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    assert_eq!(get_category("", None).await, original);
+    assert_eq!(get_category("?lang=fr", None).await, translated);
+    assert_eq!(get_category("?lang=de", Some("fr")).await, translated);
+    assert_eq!(get_category("", Some("de, fr-CA;q=0.8")).await, translated);
+    assert_eq!(get_category("", Some("de, hi;q=0.8")).await, original);
+
+    let path = admin_url_to("labels/categories/2/translations/fr");
+
+    let response = client
+        .delete(path.clone())
+        .send()
+        .await
+        .expect("delete translation");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(get_category("?lang=fr", None).await, original);
+
+    let response = client
+        .delete(path)
+        .send()
+        .await
+        .expect("delete translation again");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+async fn test_format_management() {
+    async fn get_formats() -> Vec<String> {
+        let response = reqwest::get(url_to(Some("formats".to_string())))
+            .await
+            .expect("get /formats");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+            .expect("parse response as Vec<String>")
+    }
+
+    let client = admin_client();
+    let original_formats = get_formats().await;
+
+    let webm = serde_json::json!({
+        "container": "webm",
+        "codec": "opus",
+        "extension": "webm",
+        "essence": "audio/webm",
+    });
+
+    let response = client
+        .post(admin_url_to("formats"))
+        .header("content-type", "application/json")
+        .body(webm.to_string())
+        .send()
+        .await
+        .expect("create format");
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let FormatResponse { id } =
+        serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+            .expect("deserialize format response");
+
+    let mut expected = original_formats.clone();
+    expected.push("audio/webm".to_owned());
+    assert_eq!(get_formats().await, expected);
+
+    let response = client
+        .post(admin_url_to("formats"))
+        .header("content-type", "application/json")
+        .body(webm.to_string())
+        .send()
+        .await
+        .expect("create duplicate format");
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let mut disabled = webm.clone();
+    disabled["enabled"] = serde_json::json!(false);
+
+    let response = client
+        .put(admin_url_to(&format!("formats/{}", id)))
+        .header("content-type", "application/json")
+        .body(disabled.to_string())
+        .send()
+        .await
+        .expect("disable format");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(get_formats().await, original_formats);
+
+    let response = admin_client()
+        .get(admin_url_to("formats"))
+        .send()
+        .await
+        .expect("get all formats");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let all_formats: Vec<ManagedFormat> =
+        serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+            .expect("deserialize managed formats");
+    assert_eq!(
+        all_formats.iter().find(|f| f.id == id),
+        Some(&ManagedFormat {
+            id,
+            container: "webm".to_owned(),
+            codec: "opus".to_owned(),
+            extension: "webm".to_owned(),
+            essence: "audio/webm".to_owned(),
+            enabled: false,
+        })
+    );
+
+    let path = admin_url_to(&format!("formats/{}", id));
+
+    let response = client
+        .delete(path.clone())
+        .send()
+        .await
+        .expect("delete format");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = client
+        .delete(path)
+        .send()
+        .await
+        .expect("delete format again");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+async fn test_blocklist() {
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct BlockedTerm {
+        id: i32,
+        term: String,
+        whole_word: bool,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct BlockedTermResponse {
+        id: i32,
+    }
+
+    async fn availability(name: &str) -> StatusCode {
+        let mut url = url_to(Some("available".to_owned()));
+        url.query_pairs_mut().append_pair("name", name);
+
+        reqwest::get(url).await.expect("get /available").status()
+    }
+
+    let client = admin_client();
+
+    assert_eq!(availability("Mr Bädword").await, StatusCode::OK);
+
+    let response = client
+        .post(admin_url_to("blocklist"))
+        .header("content-type", "application/json")
+        .body(serde_json::json!({ "term": "BADWORD" }).to_string())
+        .send()
+        .await
+        .expect("block term");
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let BlockedTermResponse { id } =
+        serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+            .expect("deserialize blocked term response");
+
+    let response = client
+        .post(admin_url_to("blocklist"))
+        .header("content-type", "application/json")
+        .body(serde_json::json!({ "term": "bädword" }).to_string())
+        .send()
+        .await
+        .expect("block the same term again");
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let terms: Vec<BlockedTerm> = client
+        .get(admin_url_to("blocklist"))
+        .send()
+        .await
+        .expect("get blocklist")
+        .json()
+        .await
+        .expect("deserialize blocklist");
+    assert_eq!(terms.len(), 1);
+    assert_eq!(terms[0].id, id);
+    assert_eq!(terms[0].term, "badword");
+    assert!(!terms[0].whole_word);
+
+    assert_eq!(
+        availability("Mr Bädword").await,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    let response = client
+        .delete(admin_url_to(&format!("blocklist/{}", id)))
+        .send()
+        .await
+        .expect("unblock term");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = client
+        .delete(admin_url_to(&format!("blocklist/{}", id)))
+        .send()
+        .await
+        .expect("unblock term again");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    assert_eq!(availability("Mr Bädword").await, StatusCode::OK);
+}
+
+async fn test_admin_authentication() {
+    let response = reqwest::get(admin_url_to("healthz"))
+        .await
+        .expect("get /healthz without credentials");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = reqwest::get(admin_url_to("reports"))
+        .await
+        .expect("get /reports without credentials");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = reqwest::Client::new()
+        .post(admin_url_to("terminate"))
+        .header("authorization", format!("Bearer {}x", ADMIN_TOKEN))
+        .send()
+        .await
+        .expect("terminate with the wrong token");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = reqwest::Client::new()
+        .get(admin_url_to("reports"))
+        .header("authorization", ADMIN_TOKEN)
+        .send()
+        .await
+        .expect("get /reports without the bearer scheme");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = admin_client()
+        .get(admin_url_to("reports"))
+        .send()
+        .await
+        .expect("get /reports with credentials");
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn test_readiness() {
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct ReadyzResponse {
+        ready: bool,
+        checks: std::collections::HashMap<String, Check>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Check {
+        ok: bool,
+        error: Option<String>,
+    }
+
+    // readiness probes don’t need credentials
+    let response = reqwest::get(admin_url_to("readyz"))
+        .await
+        .expect("get /readyz");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let ReadyzResponse { ready, checks } =
+        serde_json::from_slice(&response.bytes().await.expect("get response body as bytes"))
+            .expect("deserialize readiness response");
+    assert!(ready);
+
+    for dependency in &["database", "store", "ffprobe"] {
+        let check = checks
+            .get(*dependency)
+            .unwrap_or_else(|| panic!("find {} check", dependency));
+        assert!(
+            check.ok,
+            "{} should be ready: {:?}",
+            dependency, check.error
+        );
+    }
+}
+
+async fn test_metrics() {
+    let response = reqwest::get(admin_url_to("metrics"))
+        .await
+        .expect("get /metrics without credentials");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = admin_client()
+        .get(admin_url_to("metrics"))
+        .send()
+        .await
+        .expect("get /metrics");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.text().await.expect("get response body as text");
+
+    for expected in &[
+        r#"backend_requests_total{route="formats",status="200"}"#,
+        r#"backend_requests_total{route="admin::formats",status="200"}"#,
+        r#"backend_request_duration_seconds_bucket{route="upload""#,
+        r#"backend_errors_total{status="404",variant="NonExistentId"}"#,
+        "backend_upload_size_bytes_count",
+        "backend_ffprobe_duration_seconds_count",
+        r#"backend_store_duration_seconds_count{operation="put"}"#,
+        "backend_db_pool_connections",
+        "backend_db_pool_idle_connections",
+    ] {
+        assert!(
+            body.contains(expected),
+            "metrics should include {}",
+            expected
+        );
+    }
+}
+
+async fn test_bad_uploads() {
+    {
+        let response = reqwest::Client::new()
+            .request(reqwest::Method::POST, url_to(None))
+            .header("content-type", "text/plain")
+            .header("content-length", 0)
+            .send()
+            .await
+            .expect("make request");
+
+        // should fail because of `content-type`
+        let status = response.status();
+        assert!(status.is_client_error());
+        assert_eq!(status.as_u16(), 400);
+    }
+}
+
+async fn test_invalid_metadata(file_path: impl AsRef<Path>, content_type: impl AsRef<str>) {
+    use uuid::Uuid;
+
+    let metadata = serde_json::json!({
+        "name": "n".repeat(101),
+        "location": "some\u{7}where",
+        "occupation": "line\nbreak",
+        "email": "not an email",
+        "category_id": 5,
+        "age_id": 20,
+        "token": Uuid::new_v4(),
+    });
+
+    let response = upload_file(
+        &file_path,
+        content_type.as_ref(),
+        BOUNDARY.as_bytes(),
+        metadata.to_string().as_bytes(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body: serde_json::Value = response.json().await.expect("parse error response");
+    assert_eq!(body["code"], "invalid_metadata");
+
+    let problems = body["details"]["fields"]
+        .as_array()
+        .expect("get field errors")
+        .iter()
+        .map(|e| {
+            (
+                e["field"].as_str().unwrap().to_owned(),
+                e["problem"].as_str().unwrap().to_owned(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for (field, problem) in &[
+        ("name", "too_long"),
+        ("location", "control_characters"),
+        ("occupation", "control_characters"),
+        ("email", "invalid_email"),
+        ("age_id", "unknown_label"),
+        ("category_id", "unknown_label"),
+    ] {
+        assert!(
+            problems.contains(&(field.to_string(), problem.to_string())),
+            "expected {} to be {} in {:?}",
+            field,
+            problem,
+            problems
+        );
+    }
+}
+
+async fn test_non_existent_recording() {
+    use uuid::Uuid;
+
+    let path = format!("id/{id}", id = Uuid::new_v4());
+    let response = reqwest::get(url_to(Some(path.clone())))
         .await
         .expect(&format!("get {}", path));
 
     assert_eq!(response.status(), StatusCode::NOT_FOUND)
 }
 
+async fn test_request_ids() {
+    let response = reqwest::get(url_to(Some("formats".to_string())))
+        .await
+        .expect("get /formats");
+    assert!(response.headers().contains_key("x-request-id"));
+
+    // a malformed ID is rejected, which gives an error response body
+    let path = "id/not-a-uuid".to_string();
+    let response = reqwest::Client::new()
+        .get(url_to(Some(path.clone())))
+        .header("x-request-id", "test-request-id")
+        .send()
+        .await
+        .expect(&format!("get {}", path));
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response
+            .headers()
+            .get("x-request-id")
+            .expect("get X-Request-Id header"),
+        "test-request-id"
+    );
+
+    let body: serde_json::Value = response.json().await.expect("parse error response");
+    assert_eq!(body["request_id"], "test-request-id");
+    assert_eq!(body["code"], "invalid_id");
+}
+
 fn parse_children_ids(body: &[u8]) -> Vec<String> {
     #[derive(Debug, Deserialize)]
     #[serde(deny_unknown_fields)]
@@ -871,7 +2208,7 @@ fn verify_recording_data(recording: &RetrievalResponse, id: &str, parent_id: &st
         RelatedLabel(1, "یہ بھی ہے".to_owned(), None)
     );
     assert_eq!(recording.parent, Some(parent_id.to_owned()));
-    assert_eq!(recording.name, "Another \r\nname");
+    assert_eq!(recording.name, "Another name");
     assert_eq!(recording.age, None);
     assert_eq!(
         recording.gender,
@@ -905,6 +2242,37 @@ fn url_to(path: Option<String>) -> Url {
     }
 }
 
+fn admin_client() -> reqwest::Client {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        reqwest::header::AUTHORIZATION,
+        format!("Bearer {}", ADMIN_TOKEN)
+            .parse()
+            .expect("parse authorization header"),
+    );
+
+    reqwest::Client::builder()
+        .default_headers(headers)
+        .build()
+        .expect("build admin client")
+}
+
+fn admin_url_to(path: &str) -> Url {
+    lazy_static! {
+        static ref ADMIN_URL: Url = Url::parse(&format!(
+            "{}:{}",
+            std::env::var("BACKEND_TESTING_SERVER")
+                .unwrap_or_else(|_| "http://127.0.0.1".to_string()),
+            get_variable("BACKEND_ADMIN_PORT")
+        ))
+        .expect("parse URL");
+    }
+
+    ADMIN_URL
+        .join(path)
+        .expect(&format!("must join {} to {}", ADMIN_URL.as_str(), path))
+}
+
 async fn prepare_db() {
     let connection_string = get_variable("BACKEND_DB_CONNECTION_STRING");
 